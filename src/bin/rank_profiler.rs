@@ -50,8 +50,7 @@ pub fn profile_rank1(rs: &RankSupport, elem: u64, func_counter: &mut FuncCounter
     let (block_position, offset) = div_with_remainder(elem, rs.b as u64);
     let mut now = Instant::now();
     let final_bits =
-        unsafe { popcount(rs.store.get_bits((elem - offset) as usize, offset as usize) as u64) }
-            as usize;
+        popcount(rs.store.get_bits((elem - offset) as usize, offset as usize) as u64) as usize;
     func_counter.bit_count += now.elapsed();
     now = Instant::now();
     let block_rank = rs.blocks.get(block_position as usize);
@@ -1,28 +1,190 @@
-use std::{iter::StepBy, mem, path::PathBuf, rc::Rc, time::Instant};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    iter::StepBy,
+    mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Mutex,
+    time::Instant,
+};
 
 use clap::Parser;
-use eyre::{Context, Result};
+use eyre::{eyre, Context, Result};
 use rand::{
     distributions::{Bernoulli, Uniform},
     rngs::StdRng,
-    Rng, SeedableRng,
+    Rng,
 };
 use serde::Serialize;
 use sparse_array::{
-    cli::{BlockSize, RankArgs, RankSelectArgs, RankSelectCommands, SelectArgs},
-    experiment::{Experiment, ExperimentRun},
+    cli::{
+        BitDistribution, BlockSize, InputFormat, OutputFormat, RankArgs, RankSelectArgs,
+        RankSelectCommands, SelectArgs, VerifyArgs,
+    },
+    experiment::{Experiment, ExperimentRun, VerificationStats, CSV_HEADER},
     rank_support::RankSupport,
     select_support::SelectSupport,
 };
 use sucds::BitVector;
 
-pub fn generate_bitvector_of_size(size: u64, rng: &mut StdRng) -> BitVector {
-    let distribution = Bernoulli::new(0.5).unwrap();
-    BitVector::from_bits(
-        rng.sample_iter(&distribution)
-            .take(size as usize)
-            .collect::<Vec<bool>>(),
-    )
+/// Opens `outfile` and writes the CSV header when `output_format` is `Csv`,
+/// so the file is ready for [`Experiment::on_run_complete`] to append rows
+/// to as soon as the sweep starts. `None` for `Json`, which is written once
+/// at the end via [`Experiment::save`] instead.
+fn open_csv_writer(outfile: &Path, output_format: &OutputFormat) -> Mutex<Option<BufWriter<File>>> {
+    match output_format {
+        OutputFormat::Csv => {
+            let file = File::create(outfile).expect("failed to create csv output file");
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{CSV_HEADER}").expect("failed to write csv header");
+            Mutex::new(Some(writer))
+        }
+        OutputFormat::Json => Mutex::new(None),
+    }
+}
+
+/// Appends `run`'s CSV row to `writer` and flushes, so a row already on disk
+/// survives even if a later parameter point panics or the process is killed.
+fn append_csv_row(writer: &Mutex<Option<BufWriter<File>>>, run: &ExperimentRun<u64>) {
+    if let Some(writer) = writer.lock().unwrap().as_mut() {
+        writeln!(writer, "{}", run.to_csv_row()).expect("failed to append csv row");
+        writer.flush().expect("failed to flush csv row");
+    }
+}
+
+/// Parse a `--input` file into a `BitVector` according to `format`.
+fn load_bitvector(path: &Path, format: &InputFormat) -> Result<BitVector> {
+    match format {
+        InputFormat::Packed => {
+            let bytes = fs::read(path).wrap_err("failed to read packed input file")?;
+            if bytes.len() < 8 {
+                return Err(eyre!(
+                    "packed input file is too short to contain a bit count header"
+                ));
+            }
+            let mut len_bytes = [0u8; 8];
+            len_bytes.copy_from_slice(&bytes[..8]);
+            let len = u64::from_le_bytes(len_bytes);
+            let words = bytes[8..]
+                .chunks(8)
+                .map(|chunk| {
+                    let mut word = [0u8; 8];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    u64::from_le_bytes(word)
+                })
+                .collect::<Vec<u64>>();
+            let bits = (0..len)
+                .map(|i| {
+                    let word = words[(i / 64) as usize];
+                    (word >> (i % 64)) & 1 == 1
+                })
+                .collect::<Vec<bool>>();
+            Ok(BitVector::from_bits(bits))
+        }
+        InputFormat::Ascii => {
+            let text = fs::read_to_string(path).wrap_err("failed to read ascii input file")?;
+            let bits = text
+                .chars()
+                .filter(|c| *c == '0' || *c == '1')
+                .map(|c| c == '1')
+                .collect::<Vec<bool>>();
+            Ok(BitVector::from_bits(bits))
+        }
+        InputFormat::Positions => {
+            let text = fs::read_to_string(path).wrap_err("failed to read positions input file")?;
+            let positions = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| {
+                    line.trim()
+                        .parse::<u64>()
+                        .wrap_err("invalid position in positions input file")
+                })
+                .collect::<Result<Vec<u64>>>()?;
+            let len = positions.iter().max().map(|m| m + 1).unwrap_or(0);
+            let mut bits = vec![false; len as usize];
+            for p in positions {
+                bits[p as usize] = true;
+            }
+            Ok(BitVector::from_bits(bits))
+        }
+    }
+}
+
+/// Sample a geometric variate (number of failures before the first success)
+/// with the given `mean`, via inverse-transform sampling. Used to draw
+/// gap/run lengths for the `clustered` distribution below.
+fn sample_geometric(rng: &mut StdRng, mean: f64) -> u64 {
+    let p = 1.0 / (mean.max(0.0) + 1.0);
+    if p >= 1.0 {
+        return 0;
+    }
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    (u.ln() / (1.0 - p).ln()).floor().max(0.0) as u64
+}
+
+/// Set bits in contiguous bursts: alternate a gap drawn from a geometric
+/// distribution with mean `(1 - density) / density * avg_run` and a run
+/// drawn from a geometric distribution with mean `avg_run`, until `size`
+/// bits have been produced.
+fn generate_clustered_bits(size: u64, density: f64, avg_run: f64, rng: &mut StdRng) -> Vec<bool> {
+    let size = size as usize;
+    let mut bits = vec![false; size];
+    let gap_mean = ((1.0 - density) / density.max(f64::EPSILON)) * avg_run;
+    let mut pos = 0_usize;
+    while pos < size {
+        pos += sample_geometric(rng, gap_mean) as usize;
+        if pos >= size {
+            break;
+        }
+        let run_len = sample_geometric(rng, (avg_run - 1.0).max(0.0)) as usize + 1;
+        let end = (pos + run_len).min(size);
+        bits[pos..end].fill(true);
+        pos = end;
+    }
+    bits
+}
+
+/// Alternate fixed-length set/unset spans sized from `density` and
+/// `avg_run`, with no randomness in where runs fall.
+fn generate_run_bits(size: u64, density: f64, avg_run: f64) -> Vec<bool> {
+    let size = size as usize;
+    let on_len = (avg_run.max(1.0).round() as usize).max(1);
+    let off_len = if density <= 0.0 {
+        size
+    } else {
+        ((on_len as f64) * (1.0 - density) / density)
+            .round()
+            .max(0.0) as usize
+    };
+    let mut bits = Vec::with_capacity(size);
+    while bits.len() < size {
+        bits.extend(std::iter::repeat(true).take(on_len));
+        bits.extend(std::iter::repeat(false).take(off_len));
+    }
+    bits.truncate(size);
+    bits
+}
+
+pub fn generate_bitvector_of_size(
+    size: u64,
+    density: f64,
+    distribution: &BitDistribution,
+    avg_run: f64,
+    rng: &mut StdRng,
+) -> BitVector {
+    let bits = match distribution {
+        BitDistribution::Uniform => {
+            let bernoulli = Bernoulli::new(density).unwrap();
+            rng.sample_iter(&bernoulli)
+                .take(size as usize)
+                .collect::<Vec<bool>>()
+        }
+        BitDistribution::Clustered => generate_clustered_bits(size, density, avg_run, rng),
+        BitDistribution::Runs => generate_run_bits(size, density, avg_run),
+    };
+    BitVector::from_bits(bits)
 }
 
 const WORD_SIZE: usize = mem::size_of::<usize>();
@@ -32,14 +194,20 @@ pub struct RankExperiment {
     params: RankArgs,
     runs: Vec<ExperimentRun<u64>>,
     outfile: PathBuf,
+    output_format: OutputFormat,
+    #[serde(skip)]
+    csv_writer: Mutex<Option<BufWriter<File>>>,
 }
 
 impl RankExperiment {
-    pub fn new(params: RankArgs, outfile: PathBuf) -> Self {
+    pub fn new(params: RankArgs, outfile: PathBuf, output_format: OutputFormat) -> Self {
+        let csv_writer = open_csv_writer(&outfile, &output_format);
         Self {
             runs: Vec::new(),
             params,
             outfile,
+            output_format,
+            csv_writer,
         }
     }
 }
@@ -49,14 +217,20 @@ pub struct SelectExperiment {
     params: SelectArgs,
     runs: Vec<ExperimentRun<u64>>,
     outfile: PathBuf,
+    output_format: OutputFormat,
+    #[serde(skip)]
+    csv_writer: Mutex<Option<BufWriter<File>>>,
 }
 
 impl SelectExperiment {
-    pub fn new(params: SelectArgs, outfile: PathBuf) -> Self {
+    pub fn new(params: SelectArgs, outfile: PathBuf, output_format: OutputFormat) -> Self {
+        let csv_writer = open_csv_writer(&outfile, &output_format);
         Self {
             runs: Vec::new(),
             params,
             outfile,
+            output_format,
+            csv_writer,
         }
     }
 }
@@ -67,11 +241,25 @@ impl Experiment for RankExperiment {
     type I = StepBy<std::ops::RangeInclusive<u64>>;
 
     fn iter_params(&self) -> Self::I {
-        (self.params.min_size..=self.params.max_size).step_by(self.params.step_size)
+        if self.params.input.is_some() {
+            (0..=0).step_by(1)
+        } else {
+            (self.params.min_size..=self.params.max_size).step_by(self.params.step_size)
+        }
     }
 
     fn setup(&self, rng: &mut rand::rngs::StdRng, param: &Self::Param) -> Self::Resource {
-        let store = generate_bitvector_of_size(*param, rng);
+        let store = match &self.params.input {
+            Some(path) => load_bitvector(path, &self.params.input_format)
+                .expect("failed to load bitvector from --input"),
+            None => generate_bitvector_of_size(
+                *param,
+                self.params.density,
+                &self.params.distribution,
+                self.params.avg_run,
+                rng,
+            ),
+        };
         match self.params.block_size {
             BlockSize::Dynamic => RankSupport::new_from_owned(store),
             BlockSize::Fixed => RankSupport::with_block_size(WORD_SIZE as u64, Rc::new(store)),
@@ -82,7 +270,11 @@ impl Experiment for RankExperiment {
         resource.overhead()
     }
 
-    fn execute_queries(&self, rng: &mut StdRng, resource: &Self::Resource) -> std::time::Duration {
+    fn execute_queries(
+        &self,
+        rng: &mut StdRng,
+        resource: &Self::Resource,
+    ) -> Vec<std::time::Duration> {
         let query_distribution = Uniform::new_inclusive(0, resource.store.len() as u64);
         rng.sample_iter(query_distribution)
             .take(self.params.query_size as usize)
@@ -91,7 +283,11 @@ impl Experiment for RankExperiment {
                 resource.rank1(x);
                 now.elapsed()
             })
-            .sum()
+            .collect()
+    }
+
+    fn on_run_complete(&self, run: &ExperimentRun<Self::Param>) {
+        append_csv_row(&self.csv_writer, run);
     }
 }
 
@@ -101,11 +297,25 @@ impl Experiment for SelectExperiment {
     type I = StepBy<std::ops::RangeInclusive<u64>>;
 
     fn iter_params(&self) -> Self::I {
-        (self.params.min_size..=self.params.max_size).step_by(self.params.step_size)
+        if self.params.input.is_some() {
+            (0..=0).step_by(1)
+        } else {
+            (self.params.min_size..=self.params.max_size).step_by(self.params.step_size)
+        }
     }
 
     fn setup(&self, rng: &mut rand::rngs::StdRng, param: &Self::Param) -> Self::Resource {
-        let store = generate_bitvector_of_size(*param, rng);
+        let store = match &self.params.input {
+            Some(path) => load_bitvector(path, &self.params.input_format)
+                .expect("failed to load bitvector from --input"),
+            None => generate_bitvector_of_size(
+                *param,
+                self.params.density,
+                &self.params.distribution,
+                self.params.avg_run,
+                rng,
+            ),
+        };
         let rank_support = RankSupport::new_from_owned(store);
         SelectSupport::new_from_owned(rank_support)
     }
@@ -114,7 +324,11 @@ impl Experiment for SelectExperiment {
         resource.overhead()
     }
 
-    fn execute_queries(&self, rng: &mut StdRng, resource: &Self::Resource) -> std::time::Duration {
+    fn execute_queries(
+        &self,
+        rng: &mut StdRng,
+        resource: &Self::Resource,
+    ) -> Vec<std::time::Duration> {
         let query_distribution =
             Uniform::new_inclusive(0, resource.rank_support.store.len() as u64);
         rng.sample_iter(query_distribution)
@@ -124,49 +338,196 @@ impl Experiment for SelectExperiment {
                 resource.select1(x);
                 now.elapsed()
             })
-            .sum()
+            .collect()
+    }
+
+    fn on_run_complete(&self, run: &ExperimentRun<Self::Param>) {
+        append_csv_row(&self.csv_writer, run);
+    }
+}
+
+#[derive(Serialize)]
+pub struct VerifyExperiment {
+    params: VerifyArgs,
+    runs: Vec<ExperimentRun<u64>>,
+    outfile: PathBuf,
+    output_format: OutputFormat,
+    #[serde(skip)]
+    csv_writer: Mutex<Option<BufWriter<File>>>,
+}
+
+impl VerifyExperiment {
+    pub fn new(params: VerifyArgs, outfile: PathBuf, output_format: OutputFormat) -> Self {
+        let csv_writer = open_csv_writer(&outfile, &output_format);
+        Self {
+            runs: Vec::new(),
+            params,
+            outfile,
+            output_format,
+            csv_writer,
+        }
+    }
+}
+
+impl Experiment for VerifyExperiment {
+    type Resource = SelectSupport;
+    type Param = u64;
+    type I = StepBy<std::ops::RangeInclusive<u64>>;
+
+    fn iter_params(&self) -> Self::I {
+        if self.params.input.is_some() {
+            (0..=0).step_by(1)
+        } else {
+            (self.params.min_size..=self.params.max_size).step_by(self.params.step_size)
+        }
+    }
+
+    fn setup(&self, rng: &mut rand::rngs::StdRng, param: &Self::Param) -> Self::Resource {
+        let store = match &self.params.input {
+            Some(path) => load_bitvector(path, &self.params.input_format)
+                .expect("failed to load bitvector from --input"),
+            None => generate_bitvector_of_size(
+                *param,
+                self.params.density,
+                &self.params.distribution,
+                self.params.avg_run,
+                rng,
+            ),
+        };
+        let rank_support = RankSupport::new_from_owned(store);
+        SelectSupport::new_from_owned(rank_support)
+    }
+
+    fn get_overhead(&self, resource: &Self::Resource) -> u64 {
+        resource.overhead()
+    }
+
+    fn execute_queries(
+        &self,
+        rng: &mut StdRng,
+        resource: &Self::Resource,
+    ) -> Vec<std::time::Duration> {
+        let len = resource.rank_support.store.len() as u64;
+        let query_distribution = Uniform::new_inclusive(0, len);
+        rng.sample_iter(query_distribution)
+            .take(self.params.query_size as usize)
+            .map(|x: u64| {
+                let now = Instant::now();
+                let r = resource.rank_support.rank1(x);
+                resource.select1(r);
+                now.elapsed()
+            })
+            .collect()
+    }
+
+    /// Exhaustively check that rank and select remain inverses of each
+    /// other: `select1(rank1(i)) <= i` for every set position `i`,
+    /// `rank1(select1(k)) == k` for every `k` in `1..=rank1(len)`, plus the
+    /// `rank1(0)`/`rank1(len)` boundaries and out-of-range selects returning
+    /// the `len` sentinel.
+    fn verify(&self, resource: &Self::Resource) -> Option<VerificationStats> {
+        let rank_support = &resource.rank_support;
+        let store = &rank_support.store;
+        let len = store.len() as u64;
+        let mut stats = VerificationStats::default();
+        let mut check = |ok: bool| {
+            if ok {
+                stats.passed += 1;
+            } else {
+                stats.failed += 1;
+            }
+        };
+
+        for i in 0..len {
+            if store.get_bit(i as usize) {
+                check(resource.select1(rank_support.rank1(i)) <= i);
+            }
+        }
+
+        let total_ones = rank_support.rank1(len);
+        for k in 1..=total_ones {
+            check(rank_support.rank1(resource.select1(k)) == k);
+        }
+
+        check(rank_support.rank1(0) == 0);
+        check(rank_support.rank1(len) == total_ones);
+        check(resource.select1(total_ones + len + 1) == len);
+        check(rank_support.select0(len + total_ones + 1) == len);
+
+        Some(stats)
+    }
+
+    fn on_run_complete(&self, run: &ExperimentRun<Self::Param>) {
+        append_csv_row(&self.csv_writer, run);
     }
 }
 
 pub enum RankSupportExperiment {
     Rank(RankExperiment),
     Select(SelectExperiment),
+    Verify(VerifyExperiment),
 }
 
 impl RankSupportExperiment {
     pub fn new(args: RankSelectArgs) -> Self {
         match args.command {
-            RankSelectCommands::Rank(rank_args) => {
-                Self::Rank(RankExperiment::new(rank_args, args.outfile))
-            }
-            RankSelectCommands::Select(select_args) => {
-                Self::Select(SelectExperiment::new(select_args, args.outfile))
-            }
+            RankSelectCommands::Rank(rank_args) => Self::Rank(RankExperiment::new(
+                rank_args,
+                args.outfile,
+                args.output_format,
+            )),
+            RankSelectCommands::Select(select_args) => Self::Select(SelectExperiment::new(
+                select_args,
+                args.outfile,
+                args.output_format,
+            )),
+            RankSelectCommands::Verify(verify_args) => Self::Verify(VerifyExperiment::new(
+                verify_args,
+                args.outfile,
+                args.output_format,
+            )),
         }
     }
 
-    pub fn run(&mut self, rng: &mut StdRng) {
+    pub fn run(&mut self, base_seed: u64) {
         match self {
             Self::Rank(experiment) => {
-                experiment.runs.extend(experiment.create_runs(rng));
+                experiment.runs.extend(experiment.create_runs(base_seed));
             }
             Self::Select(experiment) => {
-                experiment.runs.extend(experiment.create_runs(rng));
+                experiment.runs.extend(experiment.create_runs(base_seed));
+            }
+            Self::Verify(experiment) => {
+                experiment.runs.extend(experiment.create_runs(base_seed));
             }
         }
     }
 
+    /// For JSON output this performs the one-shot whole-experiment write.
+    /// For CSV output, every row was already appended to `outfile` as it
+    /// completed (see `on_run_complete`), so there's nothing left to do here.
     pub fn save(&self) -> Result<()> {
         match self {
             Self::Rank(experiment) => {
-                experiment
-                    .save(&experiment.outfile)
-                    .wrap_err("Failed to save rank experiment results")?;
+                if matches!(experiment.output_format, OutputFormat::Json) {
+                    experiment
+                        .save(&experiment.outfile)
+                        .wrap_err("Failed to save rank experiment results")?;
+                }
             }
             Self::Select(experiment) => {
-                experiment
-                    .save(&experiment.outfile)
-                    .wrap_err("Failed to save select experiment results")?;
+                if matches!(experiment.output_format, OutputFormat::Json) {
+                    experiment
+                        .save(&experiment.outfile)
+                        .wrap_err("Failed to save select experiment results")?;
+                }
+            }
+            Self::Verify(experiment) => {
+                if matches!(experiment.output_format, OutputFormat::Json) {
+                    experiment
+                        .save(&experiment.outfile)
+                        .wrap_err("Failed to save verify experiment results")?;
+                }
             }
         }
         Ok(())
@@ -175,10 +536,15 @@ impl RankSupportExperiment {
 
 pub fn main() -> Result<()> {
     let args = RankSelectArgs::parse();
+    if args.threads > 0 {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(args.threads)
+            .build_global()
+            .wrap_err("failed to configure rayon thread pool")?;
+    }
+    let base_seed: u64 = rand::random();
     let mut experiment = RankSupportExperiment::new(args);
-    // let mut rng = StdRng::seed_from_u64(42);
-    let mut rng = StdRng::from_entropy();
-    experiment.run(&mut rng);
+    experiment.run(base_seed);
     experiment.save()?;
     Ok(())
 }
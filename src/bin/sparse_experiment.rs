@@ -1,53 +1,77 @@
-use std::{iter::StepBy, ops::RangeInclusive, path::PathBuf, time::Instant};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    iter::StepBy,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
 
-use eyre::Result;
-use rand::{distributions::Uniform, prelude::Distribution, rngs::StdRng, SeedableRng};
+use eyre::{Context, Result};
+use rand::{distributions::Uniform, prelude::Distribution};
 use serde::Serialize;
 use sparse_array::{
-    cli::{Commands, SparseArrayCli, SparseQueryMode},
-    experiment::{Experiment, ExperimentRun},
+    cli::{OutputFormat, SparseArrayArgs, SparseArrayCommands, SparseQueryMode},
+    experiment::{Experiment, ExperimentRun, CSV_HEADER},
     sparse_array::SparseArray,
 };
 
 use clap::Parser;
 
+/// Opens `outfile` and writes the CSV header when `output_format` is `Csv`,
+/// so [`Experiment::on_run_complete`] can append a row per finished run.
+/// `None` for `Json`, which is written once at the end via `save` instead.
+fn open_csv_writer(outfile: &Path, output_format: &OutputFormat) -> Mutex<Option<BufWriter<File>>> {
+    match output_format {
+        OutputFormat::Csv => {
+            let file = File::create(outfile).expect("failed to create csv output file");
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{CSV_HEADER}").expect("failed to write csv header");
+            Mutex::new(Some(writer))
+        }
+        OutputFormat::Json => Mutex::new(None),
+    }
+}
+
 #[derive(Serialize)]
 pub struct ExperimentContainer {
     pub runs: Vec<ExperimentRun<u64>>,
-    pub command: Commands,
+    pub command: SparseArrayCommands,
+    pub outfile: PathBuf,
+    pub output_format: OutputFormat,
+    #[serde(skip)]
+    csv_writer: Mutex<Option<BufWriter<File>>>,
 }
 
 impl ExperimentContainer {
-    pub fn new(command: Commands) -> Self {
+    pub fn new(command: SparseArrayCommands, outfile: PathBuf, output_format: OutputFormat) -> Self {
+        let csv_writer = open_csv_writer(&outfile, &output_format);
         Self {
             command,
             runs: Vec::new(),
+            outfile,
+            output_format,
+            csv_writer,
         }
     }
 
     pub fn get_query_mode(&self) -> &SparseQueryMode {
         match &self.command {
-            Commands::Sparsity(values) => &values.query_mode,
-            Commands::Length(values) => &values.query_mode,
+            SparseArrayCommands::Sparsity(values) => &values.query_mode,
+            SparseArrayCommands::Length(values) => &values.query_mode,
         }
     }
 
     pub fn get_query_size(&self) -> u64 {
         match &self.command {
-            Commands::Sparsity(values) => values.query_size,
-            Commands::Length(values) => values.query_size,
+            SparseArrayCommands::Sparsity(values) => values.query_size,
+            SparseArrayCommands::Length(values) => values.query_size,
         }
     }
 
-    pub fn get_outfile(&self) -> &PathBuf {
-        match &self.command {
-            Commands::Sparsity(values) => &values.outfile,
-            Commands::Length(values) => &values.outfile,
-        }
-    }
-
-    pub fn run(&mut self, rng: &mut StdRng) {
-        self.runs.extend(self.create_runs(rng));
+    pub fn run(&mut self, base_seed: u64) {
+        self.runs.extend(self.create_runs(base_seed));
     }
 }
 
@@ -58,10 +82,10 @@ impl Experiment for ExperimentContainer {
 
     fn iter_params(&self) -> Self::I {
         match &self.command {
-            Commands::Sparsity(value) => {
+            SparseArrayCommands::Sparsity(value) => {
                 (value.min_sparsity as u64..=value.max_sparsity as u64).step_by(value.step_size)
             }
-            Commands::Length(value) => {
+            SparseArrayCommands::Length(value) => {
                 (value.min_length..=value.max_length).step_by(value.step_size)
             }
         }
@@ -74,8 +98,8 @@ impl Experiment for ExperimentContainer {
     fn setup(&self, rng: &mut rand::rngs::StdRng, param: &Self::Param) -> Self::Resource {
         println!("Setting up run with parameter: {param}");
         let (sparsity, length) = match &self.command {
-            Commands::Sparsity(value) => (*param as u8, value.length),
-            Commands::Length(value) => (value.sparsity, *param),
+            SparseArrayCommands::Sparsity(value) => (*param as u8, value.length),
+            SparseArrayCommands::Length(value) => (value.sparsity, *param),
         };
         let mut builder = SparseArray::create(length);
         let distribution = Uniform::<u8>::new(0, 100);
@@ -95,16 +119,15 @@ impl Experiment for ExperimentContainer {
         &self,
         rng: &mut rand::rngs::StdRng,
         resource: &Self::Resource,
-    ) -> std::time::Duration {
+    ) -> Vec<std::time::Duration> {
         let query_mode = self.get_query_mode();
         let query_size = self.get_query_size();
         let query_distribution = match query_mode {
-            SparseQueryMode::NumElemAt | SparseQueryMode::GetAtIndex => {
+            SparseQueryMode::NumElemAt | SparseQueryMode::GetAtIndex | SparseQueryMode::Rank => {
                 Uniform::new_inclusive(0, resource.size())
             }
             SparseQueryMode::GetIndexOf => Uniform::new_inclusive(0, resource.num_elem()),
-            // QueryMode::Select => Uniform::new_inclusive(0, resource.size()),
-            // QueryMode::Rank => Uniform::new_inclusive(0, resource.num_elem()),
+            SparseQueryMode::Select => Uniform::new_inclusive(1, resource.num_elem()),
         };
         query_distribution
             .sample_iter(rng)
@@ -125,16 +148,42 @@ impl Experiment for ExperimentContainer {
                     resource.get_at_index(p);
                     now.elapsed()
                 }
+                SparseQueryMode::Rank => {
+                    let now = Instant::now();
+                    resource.rank_support().rank1(p);
+                    now.elapsed()
+                }
+                SparseQueryMode::Select => {
+                    let now = Instant::now();
+                    resource.select_support().select1(p);
+                    now.elapsed()
+                }
             })
-            .sum()
+            .collect()
+    }
+
+    fn on_run_complete(&self, run: &ExperimentRun<Self::Param>) {
+        if let Some(writer) = self.csv_writer.lock().unwrap().as_mut() {
+            writeln!(writer, "{}", run.to_csv_row()).expect("failed to append csv row");
+            writer.flush().expect("failed to flush csv row");
+        }
     }
 }
 
 pub fn main() -> Result<()> {
-    let args = SparseArrayCli::parse();
-    let mut experiment = ExperimentContainer::new(args.command);
-    let mut rng = StdRng::seed_from_u64(42);
-    experiment.run(&mut rng);
-    experiment.save(experiment.get_outfile())?;
+    let args = SparseArrayArgs::parse();
+    if args.threads > 0 {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(args.threads)
+            .build_global()
+            .wrap_err("failed to configure rayon thread pool")?;
+    }
+    let mut experiment = ExperimentContainer::new(args.command, args.outfile, args.output_format);
+    experiment.run(42);
+    if matches!(experiment.output_format, OutputFormat::Json) {
+        experiment
+            .save(&experiment.outfile)
+            .wrap_err("Failed to save sparse array experiment results")?;
+    }
     Ok(())
 }
@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use core::cmp::Ordering;
 
 pub fn bisect_left<F>(mut left: u64, mut right: u64, mut f: F) -> u64
 where
@@ -13,6 +13,7 @@ pub enum QueryMode {
 pub enum RankSelectCommands {
     Rank(RankArgs),
     Select(SelectArgs),
+    Verify(VerifyArgs),
 }
 
 #[derive(ValueEnum, Debug, Clone, Serialize)]
@@ -21,12 +22,60 @@ pub enum BlockSize {
     Fixed,
 }
 
+/// How set bits are scattered across a synthetic bitvector generated for the
+/// rank/select experiments.
+#[derive(ValueEnum, Debug, Clone, Serialize)]
+pub enum BitDistribution {
+    /// Each bit is set independently with probability `density` (the prior
+    /// behavior).
+    Uniform,
+    /// Set bits come in contiguous bursts: alternating gap/run lengths drawn
+    /// from geometric distributions, so runs of consecutive ones are common
+    /// rather than vanishingly rare.
+    Clustered,
+    /// Alternating fixed-length set/unset spans, sized from `density` and
+    /// `avg_run`, with no randomness in the placement.
+    Runs,
+}
+
+/// How an experiment's results are written to `outfile`.
+#[derive(ValueEnum, Debug, Clone, Serialize)]
+pub enum OutputFormat {
+    /// Serialize the whole experiment (params + every run) as one JSON
+    /// document, written once after the sweep completes.
+    Json,
+    /// Flatten each run to one CSV row and append it to `outfile` as soon as
+    /// it completes, so partial results survive an interrupted sweep.
+    Csv,
+}
+
+/// How to interpret the bytes of a `--input` file as a bitvector.
+#[derive(ValueEnum, Debug, Clone, Serialize)]
+pub enum InputFormat {
+    /// Packed little-endian binary: an 8-byte little-endian `u64` bit count
+    /// followed by the bits themselves, 64 to a word.
+    Packed,
+    /// ASCII text made up of `0`/`1` characters, one bit per character.
+    Ascii,
+    /// One set-bit index per line, strictly increasing; the bitvector's
+    /// length is the last index plus one.
+    Positions,
+}
+
 #[derive(Parser, Debug, Serialize)]
 pub struct RankSelectArgs {
     #[command(subcommand)]
     pub command: RankSelectCommands,
 
     pub outfile: PathBuf,
+
+    #[arg(long, default_value = "0")]
+    /// Cap the worker thread pool used for parallel parameter sweeps (0 = rayon's default)
+    pub threads: usize,
+
+    #[arg(long, default_value = "json", value_enum)]
+    /// Format to write `outfile` in
+    pub output_format: OutputFormat,
 }
 
 #[derive(Args, Debug, Clone, Serialize)]
@@ -47,6 +96,26 @@ pub struct RankArgs {
 
     #[arg(short, long, default_value = "dynamic", value_enum)]
     pub block_size: BlockSize,
+
+    #[arg(long, default_value = "0.5")]
+    /// Fraction of bits that are set
+    pub density: f64,
+
+    #[arg(long, default_value = "uniform", value_enum)]
+    pub distribution: BitDistribution,
+
+    #[arg(long, default_value = "8")]
+    /// Mean run/gap length used by the `clustered` and `runs` distributions
+    pub avg_run: f64,
+
+    #[arg(long, conflicts_with_all = ["min_size", "max_size", "step_size"])]
+    /// Load the bitvector from a file instead of generating one, running the
+    /// experiment once rather than sweeping over a size range
+    pub input: Option<PathBuf>,
+
+    #[arg(long, default_value = "positions", value_enum)]
+    /// How to parse `--input`
+    pub input_format: InputFormat,
 }
 
 #[derive(Args, Debug, Clone, Serialize)]
@@ -67,6 +136,68 @@ pub struct SelectArgs {
 
     #[arg(short, long, default_value = "dynamic", value_enum)]
     pub block_size: BlockSize,
+
+    #[arg(long, default_value = "0.5")]
+    /// Fraction of bits that are set
+    pub density: f64,
+
+    #[arg(long, default_value = "uniform", value_enum)]
+    pub distribution: BitDistribution,
+
+    #[arg(long, default_value = "8")]
+    /// Mean run/gap length used by the `clustered` and `runs` distributions
+    pub avg_run: f64,
+
+    #[arg(long, conflicts_with_all = ["min_size", "max_size", "step_size"])]
+    /// Load the bitvector from a file instead of generating one, running the
+    /// experiment once rather than sweeping over a size range
+    pub input: Option<PathBuf>,
+
+    #[arg(long, default_value = "positions", value_enum)]
+    /// How to parse `--input`
+    pub input_format: InputFormat,
+}
+
+#[derive(Args, Debug, Clone, Serialize)]
+pub struct VerifyArgs {
+    #[arg(long, default_value = "1000")]
+    /// The minimum length of the array to build
+    pub min_size: u64,
+
+    #[arg(long, default_value = "100000")]
+    /// The maximum length of the array to build
+    pub max_size: u64,
+
+    #[arg(short, long, default_value = "1000")]
+    pub step_size: usize,
+
+    #[arg(short, long, default_value = "100")]
+    /// Number of random positions to time per size point, independent of the
+    /// exhaustive correctness checks below
+    pub query_size: u64,
+
+    #[arg(short, long, default_value = "dynamic", value_enum)]
+    pub block_size: BlockSize,
+
+    #[arg(long, default_value = "0.5")]
+    /// Fraction of bits that are set
+    pub density: f64,
+
+    #[arg(long, default_value = "uniform", value_enum)]
+    pub distribution: BitDistribution,
+
+    #[arg(long, default_value = "8")]
+    /// Mean run/gap length used by the `clustered` and `runs` distributions
+    pub avg_run: f64,
+
+    #[arg(long, conflicts_with_all = ["min_size", "max_size", "step_size"])]
+    /// Load the bitvector from a file instead of generating one, running the
+    /// experiment once rather than sweeping over a size range
+    pub input: Option<PathBuf>,
+
+    #[arg(long, default_value = "positions", value_enum)]
+    /// How to parse `--input`
+    pub input_format: InputFormat,
 }
 
 #[derive(ValueEnum, Clone, Debug, Serialize)]
@@ -74,6 +205,8 @@ pub enum SparseQueryMode {
     NumElemAt,
     GetAtIndex,
     GetIndexOf,
+    Rank,
+    Select,
 }
 
 #[derive(Parser, Debug, Serialize)]
@@ -104,6 +237,14 @@ pub struct SparseArrayArgs {
     pub command: SparseArrayCommands,
 
     pub outfile: PathBuf,
+
+    #[arg(long, default_value = "0")]
+    /// Cap the worker thread pool used for parallel parameter sweeps (0 = rayon's default)
+    pub threads: usize,
+
+    #[arg(long, default_value = "json", value_enum)]
+    /// Format to write `outfile` in
+    pub output_format: OutputFormat,
 }
 
 #[derive(Subcommand, Debug, Clone, Serialize)]
@@ -0,0 +1,307 @@
+use std::rc::Rc;
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sucds::{BitVector, CompactVector, Searial};
+
+use crate::{
+    binary_search::bisect_left,
+    math::log2_ceil,
+    rank_support::RankSupport,
+    serial::{from_bytes, to_bytes},
+};
+
+/// Builder that accumulates sorted set-bit positions before finalizing into
+/// an [`EliasFano`] structure.
+#[derive(Debug)]
+pub struct EliasFanoBuilder {
+    n: u64,
+    positions: Vec<u64>,
+}
+
+impl EliasFanoBuilder {
+    pub fn new(n: u64) -> Self {
+        Self {
+            n,
+            positions: Vec::new(),
+        }
+    }
+
+    /// Append the next set-bit position. Positions must be appended in
+    /// non-decreasing order, matching the sorted universe Elias-Fano encodes.
+    pub fn append(&mut self, pos: u64) {
+        self.positions.push(pos);
+    }
+
+    pub fn finalize(self) -> EliasFano {
+        EliasFano::from_sorted_positions(self.n, self.positions)
+    }
+}
+
+/// Succinct Elias-Fano encoding of a sorted set of positions `p_0 < p_1 <
+/// ... < p_{m-1}` drawn from `[0, n)`, for genuinely sparse bit vectors where
+/// a dense [`RankSupport`]/[`crate::select_support::SelectSupport`] pair
+/// would waste `n` bits storing mostly zeros.
+///
+/// Each position is split into a high part `pos >> l` and a low part `pos &
+/// ((1 << l) - 1)` with `l = log2_ceil(n / m)`. Low parts are packed
+/// contiguously into a [`CompactVector`]; high parts are encoded as a unary
+/// bitstream (a `1` for each increment in high value, a `0` for each
+/// element) over which a [`RankSupport`] is built, so this exposes the same
+/// `rank1`/`select1` query API as the dense backend at ~`2 + log2(n / m)`
+/// bits per element instead of `n` bits.
+#[derive(Debug, Clone)]
+pub struct EliasFano {
+    n: u64,
+    m: u64,
+    l: u32,
+    low: CompactVector,
+    upper: Rc<RankSupport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveableEliasFano {
+    n: u64,
+    m: u64,
+    l: u32,
+    low: Vec<u8>,
+    upper: Vec<u8>,
+}
+
+impl TryFrom<EliasFano> for SaveableEliasFano {
+    type Error = eyre::Report;
+    fn try_from(value: EliasFano) -> Result<Self> {
+        Ok(Self {
+            n: value.n,
+            m: value.m,
+            l: value.l,
+            low: to_bytes(&value.low)?,
+            upper: value.upper.to_bytes()?,
+        })
+    }
+}
+
+impl TryFrom<SaveableEliasFano> for EliasFano {
+    type Error = eyre::Report;
+    fn try_from(value: SaveableEliasFano) -> Result<Self> {
+        let low: CompactVector = from_bytes(value.low)?;
+        let upper = Rc::new(RankSupport::from_bytes(&value.upper)?);
+        Ok(Self {
+            n: value.n,
+            m: value.m,
+            l: value.l,
+            low,
+            upper,
+        })
+    }
+}
+
+impl EliasFano {
+    fn low_width(n: u64, m: u64) -> u32 {
+        if m == 0 {
+            return 0;
+        }
+        log2_ceil((n / m).max(1)) as u32
+    }
+
+    fn from_sorted_positions(n: u64, positions: Vec<u64>) -> Self {
+        let m = positions.len() as u64;
+        let l = Self::low_width(n, m);
+        let mask = if l == 0 { 0 } else { (1_u64 << l) - 1 };
+
+        let mut low = CompactVector::with_capacity(positions.len(), l.max(1) as usize);
+        let mut upper_bits = Vec::<bool>::with_capacity(positions.len() * 2);
+        let mut prev_high = 0_u64;
+        for &pos in &positions {
+            let high = pos >> l;
+            low.push((pos & mask) as usize);
+            while prev_high < high {
+                upper_bits.push(true);
+                prev_high += 1;
+            }
+            upper_bits.push(false);
+        }
+        let upper = Rc::new(RankSupport::new_from_owned(BitVector::from_bits(upper_bits)));
+
+        Self {
+            n,
+            m,
+            l,
+            low,
+            upper,
+        }
+    }
+
+    pub fn builder(n: u64) -> EliasFanoBuilder {
+        EliasFanoBuilder::new(n)
+    }
+
+    fn low_mask(&self) -> u64 {
+        if self.l == 0 {
+            0
+        } else {
+            (1_u64 << self.l) - 1
+        }
+    }
+
+    /// The number of stored positions with high part strictly less than `h`.
+    fn bucket_rank(&self, h: u64) -> u64 {
+        if h == 0 {
+            return 0;
+        }
+        let total_ones = self.upper.rank1(self.upper.store.len() as u64);
+        if h > total_ones {
+            return self.m;
+        }
+        // `select1(h)` lands one past the h-th increment of the high value,
+        // so the zeros (elements) counted before it are exactly those whose
+        // high part is still < h.
+        self.upper.rank0(self.upper.select1(h))
+    }
+
+    /// The universe size this structure was built over.
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.m == 0
+    }
+
+    /// The number of stored (set-bit) positions.
+    pub fn num_elem(&self) -> u64 {
+        self.m
+    }
+
+    /// The number of stored positions strictly less than `p`.
+    pub fn rank1(&self, p: u64) -> u64 {
+        if self.m == 0 {
+            return 0;
+        }
+        let p = p.min(self.n);
+        let high_p = p >> self.l;
+        let low_p = p & self.low_mask();
+        let bucket_start = self.bucket_rank(high_p);
+        let bucket_end = self.bucket_rank(high_p + 1);
+        bisect_left(bucket_start, bucket_end, |idx| {
+            (self.low.get(idx as usize) as u64).cmp(&low_p)
+        })
+    }
+
+    /// The position of the `i`-th (0-indexed) stored position, or `self.len()`
+    /// if `i >= num_elem()`.
+    pub fn select1(&self, i: u64) -> u64 {
+        if i >= self.m {
+            return self.n;
+        }
+        // `select0(i + 1)` lands one past the i-th (0-indexed) zero marker;
+        // stepping back one position gives that marker itself.
+        let pos = self.upper.select0(i + 1) - 1;
+        let high = pos - i;
+        let low = self.low.get(i as usize) as u64;
+        (high << self.l) | low
+    }
+
+    /// Approximate size in bits: the packed low parts, the upper bitstream
+    /// and its [`RankSupport`]'s overhead, i.e. roughly `m * (2 + log2(n / m))`
+    /// bits plus the rank index instead of the `n` bits a dense bitvector
+    /// would need.
+    pub fn overhead(&self) -> u64 {
+        (self.low.size_in_bytes() as u64 * 8)
+            + (self.upper.store.size_in_bytes() as u64 * 8)
+            + self.upper.overhead()
+    }
+
+    pub fn into_bytes(self) -> Result<Vec<u8>> {
+        let saveable: SaveableEliasFano = self.try_into()?;
+        bincode::serialize(&saveable).wrap_err("Failed to serialize EliasFano")
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.clone().into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let saveable: SaveableEliasFano =
+            bincode::deserialize(bytes).wrap_err("Failed to deserialize EliasFano")?;
+        saveable.try_into()
+    }
+
+    pub fn save(&self, fname: &str) -> Result<()> {
+        use std::{fs::File, io::BufWriter};
+        let file = File::create(fname).wrap_err(format!("Failed to create file {fname}"))?;
+        let mut writer = BufWriter::new(file);
+        let saveable: SaveableEliasFano = self.clone().try_into()?;
+        bincode::serialize_into(&mut writer, &saveable)?;
+        Ok(())
+    }
+
+    pub fn load(fname: &str) -> Result<Self> {
+        use std::{fs::File, io::BufReader};
+        let file = File::open(fname).wrap_err(format!("Failed to open file {fname}"))?;
+        let reader = BufReader::new(file);
+        let saveable: SaveableEliasFano = bincode::deserialize_from(reader)?;
+        saveable.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{distributions::Uniform, prelude::Distribution, rngs::StdRng, SeedableRng};
+
+    fn build(n: u64, positions: &[u64]) -> EliasFano {
+        let mut builder = EliasFano::builder(n);
+        positions.iter().for_each(|&p| builder.append(p));
+        builder.finalize()
+    }
+
+    #[test]
+    fn test_select1() {
+        let ef = build(20, &[1, 2, 4, 9, 15]);
+        assert_eq!(1, ef.select1(0));
+        assert_eq!(2, ef.select1(1));
+        assert_eq!(4, ef.select1(2));
+        assert_eq!(9, ef.select1(3));
+        assert_eq!(15, ef.select1(4));
+        assert_eq!(20, ef.select1(5));
+    }
+
+    #[test]
+    fn test_rank1() {
+        let ef = build(20, &[1, 2, 4, 9, 15]);
+        assert_eq!(0, ef.rank1(0));
+        assert_eq!(0, ef.rank1(1));
+        assert_eq!(1, ef.rank1(2));
+        assert_eq!(2, ef.rank1(4));
+        assert_eq!(3, ef.rank1(5));
+        assert_eq!(3, ef.rank1(9));
+        assert_eq!(5, ef.rank1(20));
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let n = 100_000_u64;
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut positions: Vec<u64> = Uniform::new(0, n)
+            .sample_iter(&mut rng)
+            .take(500)
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let ef = build(n, &positions);
+        for (rank, &pos) in positions.iter().enumerate() {
+            assert_eq!(pos, ef.select1(rank as u64));
+            assert_eq!(rank as u64, ef.rank1(pos));
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let ef = build(20, &[1, 2, 4, 9, 15]);
+        ef.save("tmp-elias-fano.bin").unwrap();
+        let loaded = EliasFano::load("tmp-elias-fano.bin").unwrap();
+        assert_eq!(ef.select1(2), loaded.select1(2));
+        assert_eq!(ef.rank1(10), loaded.rank1(10));
+    }
+}
@@ -7,7 +7,8 @@ use std::{
 };
 
 use eyre::{Context, Result};
-use rand::rngs::StdRng;
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
 use serde::Serialize;
 
 #[derive(Serialize, Debug)]
@@ -15,7 +16,113 @@ pub struct ExperimentRun<P> {
     overhead: u64,
     parameter: P,
     setup_duration: Duration,
-    query_duration: Duration,
+    query_stats: QueryStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verification: Option<VerificationStats>,
+}
+
+/// Stable CSV header matching the column order of [`ExperimentRun::to_csv_row`].
+pub const CSV_HEADER: &str = "parameter,overhead,setup_duration_ns,query_min_ns,query_max_ns,query_mean_ns,query_median_ns,query_p95_ns,query_p99_ns,query_total_ns,verification_passed,verification_failed";
+
+impl<P: std::fmt::Display> ExperimentRun<P> {
+    /// Flattens this run into one CSV row matching [`CSV_HEADER`]. Verification
+    /// columns are left at `0` when the experiment doesn't run that check.
+    pub fn to_csv_row(&self) -> String {
+        let (passed, failed) = self
+            .verification
+            .as_ref()
+            .map(|v| (v.passed, v.failed))
+            .unwrap_or((0, 0));
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.parameter,
+            self.overhead,
+            self.setup_duration.as_nanos(),
+            self.query_stats.min.as_nanos(),
+            self.query_stats.max.as_nanos(),
+            self.query_stats.mean.as_nanos(),
+            self.query_stats.median.as_nanos(),
+            self.query_stats.p95.as_nanos(),
+            self.query_stats.p99.as_nanos(),
+            self.query_stats.total.as_nanos(),
+            passed,
+            failed,
+        )
+    }
+}
+
+/// Pass/fail tally from [`Experiment::verify`]'s structural-invariant checks,
+/// so a regression in the rank/select math shows up as data in the run's
+/// output rather than a panic partway through the sweep.
+#[derive(Serialize, Debug, Default)]
+pub struct VerificationStats {
+    pub passed: u64,
+    pub failed: u64,
+}
+
+/// Summary statistics over a run's per-query latencies, computed once the
+/// full `Vec<Duration>` is in hand rather than folded incrementally, so the
+/// tail (p95/p99) is visible alongside the mean instead of being averaged
+/// away.
+#[derive(Serialize, Debug)]
+pub struct QueryStats {
+    min: Duration,
+    max: Duration,
+    mean: Duration,
+    median: Duration,
+    p95: Duration,
+    p99: Duration,
+    total: Duration,
+}
+
+impl QueryStats {
+    /// Sorts `durations` and derives min/max/mean/median/p95/p99/total from
+    /// it. Percentiles are computed by indexing the sorted vector at
+    /// `p * (n - 1)` with linear interpolation between the two adjacent
+    /// samples. An empty `durations` yields all-zero stats.
+    pub fn from_durations(mut durations: Vec<Duration>) -> Self {
+        if durations.is_empty() {
+            return Self {
+                min: Duration::ZERO,
+                max: Duration::ZERO,
+                mean: Duration::ZERO,
+                median: Duration::ZERO,
+                p95: Duration::ZERO,
+                p99: Duration::ZERO,
+                total: Duration::ZERO,
+            };
+        }
+        durations.sort();
+        let total: Duration = durations.iter().sum();
+        let mean = total / durations.len() as u32;
+        Self {
+            min: durations[0],
+            max: durations[durations.len() - 1],
+            mean,
+            median: percentile(&durations, 0.5),
+            p95: percentile(&durations, 0.95),
+            p99: percentile(&durations, 0.99),
+            total,
+        }
+    }
+}
+
+/// Linearly interpolated percentile of an already-sorted, non-empty slice.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let n = sorted.len();
+    if n == 1 {
+        return sorted[0];
+    }
+    let rank = p * (n - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    if lo == hi {
+        return sorted[lo];
+    }
+    let frac = rank - lo as f64;
+    let lo_dur = sorted[lo];
+    let hi_dur = sorted[hi];
+    lo_dur + Duration::from_secs_f64((hi_dur.as_secs_f64() - lo_dur.as_secs_f64()) * frac)
 }
 
 #[derive(Serialize, Debug)]
@@ -54,7 +161,22 @@ pub trait Experiment: Serialize {
     fn setup(&self, rng: &mut StdRng, param: &Self::Param) -> Self::Resource;
     fn get_overhead(&self, resource: &Self::Resource) -> u64;
     fn iter_params(&self) -> Self::I;
-    fn execute_queries(&self, rng: &mut StdRng, resource: &Self::Resource) -> Duration;
+    fn execute_queries(&self, rng: &mut StdRng, resource: &Self::Resource) -> Vec<Duration>;
+
+    /// Exhaustively re-check the resource's structural invariants, e.g. that
+    /// rank and select remain inverses. `None` by default; experiments that
+    /// want the correctness pass (rather than just the timing one) override
+    /// this and return `Some`.
+    fn verify(&self, _resource: &Self::Resource) -> Option<VerificationStats> {
+        None
+    }
+
+    /// Called from [`Experiment::create_runs`] as soon as each run finishes.
+    /// A no-op by default; experiments that stream results to disk (e.g. CSV
+    /// output) override this to append the run's row immediately rather than
+    /// waiting for the whole sweep to buffer in `Vec<ExperimentRun>`.
+    fn on_run_complete(&self, _run: &ExperimentRun<Self::Param>) {}
+
     fn save<S: AsRef<Path>>(&self, fname: S) -> Result<()> {
         let file = File::create(fname).wrap_err("could not create experiment output file")?;
         let mut writer = BufWriter::new(file);
@@ -70,18 +192,42 @@ pub trait Experiment: Serialize {
         let resource = self.setup(rng, &parameter);
         let setup_duration = now.elapsed();
         let overhead = self.get_overhead(&resource);
-        let query_duration = self.execute_queries(rng, &resource);
+        let query_durations = self.execute_queries(rng, &resource);
+        let verification = self.verify(&resource);
         ExperimentRun {
             overhead,
             parameter,
             setup_duration,
-            query_duration,
+            query_stats: QueryStats::from_durations(query_durations),
+            verification,
         }
     }
 
-    fn create_runs(&self, rng: &mut StdRng) -> Vec<ExperimentRun<Self::Param>> {
+    /// Runs every parameter point in parallel, one rayon task per point.
+    /// Each point gets its own `StdRng` seeded from `base_seed XOR param`, so
+    /// the setup/query timings for a given resource stay single-threaded
+    /// (avoiding scheduling noise in the latency measurements) while results
+    /// stay reproducible for a fixed `base_seed` regardless of how rayon
+    /// schedules the work. Results are collected back in parameter order.
+    ///
+    /// `Self::Resource` is created by `setup` and consumed by
+    /// `execute_queries`/`get_overhead` entirely within one task's closure
+    /// invocation, so it never actually crosses a thread boundary and does
+    /// not need to be `Send` even though the closure itself does.
+    fn create_runs(&self, base_seed: u64) -> Vec<ExperimentRun<Self::Param>>
+    where
+        Self: Sync,
+        Self::Param: Send + Copy + Into<u64>,
+    {
         self.iter_params()
-            .map(|p| self.execute_run(rng, p))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|p| {
+                let mut rng = StdRng::seed_from_u64(base_seed ^ p.into());
+                let run = self.execute_run(&mut rng, p);
+                self.on_run_complete(&run);
+                run
+            })
             .collect()
     }
 }
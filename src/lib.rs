@@ -0,0 +1,30 @@
+// NOTE: `no_std` here is aspirational, not verified. `rank_support` and
+// `sparse_array` reach for `sucds::{BitVector, CompactVector}` unconditionally
+// in the part of the query core that's meant to be `no_std`-compatible, and
+// `sucds` itself links against `std`. Disabling the `std` feature drops this
+// crate's own file I/O/mmap/`eyre` usage, but does not get you a bitvector
+// that will link on a bare-metal target — that needs a `no_std` bitset
+// replacing `sucds` in the core, which hasn't been done.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
+
+pub mod binary_search;
+pub mod math;
+pub mod rank_support;
+pub mod select_support;
+pub mod sparse_array;
+
+// Only the rank/select/sparse-array query core above is `no_std` + `alloc`
+// compatible so far; these still depend on file I/O, `eyre`, or mmap.
+#[cfg(feature = "std")]
+pub mod args;
+#[cfg(feature = "std")]
+pub mod cli;
+#[cfg(feature = "std")]
+pub mod elias_fano;
+#[cfg(feature = "std")]
+pub mod experiment;
+#[cfg(feature = "std")]
+pub mod serial;
@@ -1,5 +1,16 @@
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+/// `ceil(log2(n))`, computed from the integer's bit length rather than
+/// floating-point `log2` so this stays available under `no_std` (no `libm`
+/// dependency) and is exact for every `u64`, including values too large to
+/// round-trip through `f32`.
 pub fn log2_ceil(n: u64) -> u64 {
-    (n as f32).log2().ceil() as u64
+    if n <= 1 {
+        0
+    } else {
+        64 - (n - 1).leading_zeros() as u64
+    }
 }
 
 pub fn ceil_div(a: u64, b: u64) -> u64 {
@@ -12,17 +23,69 @@ pub fn div_with_remainder(a: u64, b: u64) -> (u64, u64) {
     (div, rem)
 }
 
-// https://eugene-babichenko.github.io/blog/2019/11/13/rust-popcount-intrinsics/
-#[inline(never)]
-#[cfg_attr(target_arch = "x86_64", target_feature(enable = "popcnt"))]
-/// Count the number of ones in the binary representation of the target integer
-///
-/// # Safety
+/// Branch-free broadword (SWAR) popcount, used on targets without a hardware
+/// popcount instruction.
 ///
-/// This library depends on a single popcnt instruction to support constant time
-/// rank queries. Use of this library on a machine that does not include the instruction
-/// is undefined behavior
+/// https://en.wikipedia.org/wiki/Hamming_weight#Efficient_implementation
+fn popcount_swar(mut x: u64) -> u32 {
+    const M1: u64 = 0x5555555555555555;
+    const M2: u64 = 0x3333333333333333;
+    const M4: u64 = 0x0f0f0f0f0f0f0f0f;
+    const H01: u64 = 0x0101010101010101;
+    x -= (x >> 1) & M1;
+    x = (x & M2) + ((x >> 2) & M2);
+    x = (x + (x >> 4)) & M4;
+    ((x.wrapping_mul(H01)) >> 56) as u32
+}
+
+// Runtime feature detection (`is_x86_feature_detected!`) is itself a std
+// facility, so under `no_std` we can't cache a detection result and always
+// fall back to the portable SWAR implementation below.
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+fn has_hardware_popcount() -> bool {
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+    *DETECTED.get_or_init(|| std::is_x86_feature_detected!("popcnt"))
+}
+
+#[cfg(not(all(feature = "std", target_arch = "x86_64")))]
+fn has_hardware_popcount() -> bool {
+    false
+}
+
+// https://eugene-babichenko.github.io/blog/2019/11/13/rust-popcount-intrinsics/
+/// Count the number of ones in the binary representation of the target integer.
 ///
-pub unsafe fn popcount(x: u64) -> u32 {
-    x.count_ones()
+/// Dispatches to the hardware `popcnt` instruction when the running CPU supports
+/// it (detected once and cached), and otherwise falls back to a portable
+/// broadword implementation so this stays sound on ARM, older x86, and WASM.
+pub fn popcount(x: u64) -> u32 {
+    if has_hardware_popcount() {
+        #[cfg(all(feature = "std", target_arch = "x86_64"))]
+        {
+            // `count_ones` only lowers to `popcnt` inside a function compiled
+            // with that target feature enabled.
+            #[target_feature(enable = "popcnt")]
+            unsafe fn popcount_hardware(x: u64) -> u32 {
+                x.count_ones()
+            }
+            // SAFETY: `popcnt` support was detected at runtime above.
+            return unsafe { popcount_hardware(x) };
+        }
+    }
+    popcount_swar(x)
+}
+
+/// Popcount a span of bits wider than a single `u64`, given as whole 64-bit
+/// `words` plus the final `partial_bits` of `partial_word` (0..=63 of its low
+/// bits). Counts every whole word and masks only the trailing partial one, so
+/// callers don't need to fetch the span through `get_bits`, which is limited
+/// to 64 bits at a time.
+pub fn rank_bits_in_range(words: &[u64], partial_word: u64, partial_bits: u32) -> u64 {
+    let whole: u64 = words.iter().map(|&word| popcount(word) as u64).sum();
+    let partial_mask = if partial_bits == 0 {
+        0
+    } else {
+        partial_word & ((1_u64 << partial_bits) - 1)
+    };
+    whole + popcount(partial_mask) as u64
 }
@@ -1,61 +1,98 @@
 use crate::{
-    math::{ceil_div, div_with_remainder, log2_ceil, popcount},
-    serial::{from_bytes, into_bytes, to_bytes},
+    binary_search::bisect_left,
+    math::{ceil_div, div_with_remainder, log2_ceil, rank_bits_in_range},
 };
-use eyre::{Context, Result};
+#[cfg(feature = "std")]
+use crate::serial::{aligned_len, bytes_as_u64_slice, from_bytes, into_bytes, to_bytes};
+#[cfg(feature = "std")]
+use eyre::{eyre, Context, Result};
+#[cfg(feature = "std")]
+use memmap2::Mmap;
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
 use std::{
-    cmp::{max, min},
     fs::File,
-    io::{BufReader, BufWriter},
+    io::{BufReader, BufWriter, Write},
     rc::Rc,
 };
-use std::mem::size_of;
+#[cfg(not(feature = "std"))]
+use alloc::{rc::Rc, vec::Vec};
+use core::cmp::{max, min};
+use core::mem::size_of;
+// `sucds` is itself a std-only crate, so it's the reason the `no_std` gating
+// on this module (see the crate root) is aspirational rather than something
+// that actually links on a bare-metal target.
 use sucds::{BitVector, CompactVector, Searial};
 
+/// Number of set (or unset) bits between consecutive select samples.
+///
+/// [`RankSupport::select1`]/[`RankSupport::select0`] narrow their `bisect_left`
+/// search to the positions between two consecutive samples, a window holding
+/// this many ones/zeros, so a query costs O(log w) rank evaluations for a
+/// window `w` positions wide instead of O(log n).
+pub const SAMPLE_SPACING: u64 = 4096;
+
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SaveableRankSupport {
     pub store: Vec<u8>,
     pub superblocks: Vec<u8>,
     pub blocks: Vec<u8>,
+    pub ones_samples: Vec<u8>,
+    pub zeros_samples: Vec<u8>,
     pub s: u16,
     pub b: u8,
+    pub sample_spacing: u64,
 }
 
+#[cfg(feature = "std")]
 impl TryFrom<RankSupport> for SaveableRankSupport {
     type Error = eyre::Report;
     fn try_from(value: RankSupport) -> eyre::Result<Self> {
         let superblocks = into_bytes(value.superblocks)?;
         let blocks = into_bytes(value.blocks)?;
+        let ones_samples = into_bytes(value.ones_samples)?;
+        let zeros_samples = into_bytes(value.zeros_samples)?;
         let store = to_bytes(value.store.as_ref())?;
         Ok(Self {
             store,
             superblocks,
             blocks,
+            ones_samples,
+            zeros_samples,
             b: value.b,
             s: value.s,
+            sample_spacing: value.sample_spacing,
         })
     }
 }
 
+#[cfg(feature = "std")]
 impl TryFrom<SaveableRankSupport> for RankSupport {
     type Error = eyre::Report;
     fn try_from(value: SaveableRankSupport) -> Result<Self, Self::Error> {
         let store: BitVector = from_bytes(value.store)?;
         let superblocks: CompactVector = from_bytes(value.superblocks)?;
         let blocks: CompactVector = from_bytes(value.blocks)?;
+        let ones_samples: CompactVector = from_bytes(value.ones_samples)?;
+        let zeros_samples: CompactVector = from_bytes(value.zeros_samples)?;
         Ok(Self {
             store: Rc::new(store),
             superblocks,
             blocks,
+            ones_samples,
+            zeros_samples,
             s: value.s,
             b: value.b,
+            sample_spacing: value.sample_spacing,
         })
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(try_from = "SaveableRankSupport")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "std", derive(Deserialize))]
+#[cfg_attr(feature = "std", serde(try_from = "SaveableRankSupport"))]
 pub struct RankSupport {
     pub store: Rc<BitVector>,
 
@@ -64,6 +101,59 @@ pub struct RankSupport {
     pub blocks: CompactVector,
     pub b: u8,
     pub s: u16,
+
+    // sampled select index: position of every `sample_spacing`-th one/zero
+    pub ones_samples: CompactVector,
+    pub zeros_samples: CompactVector,
+    pub sample_spacing: u64,
+}
+
+/// Popcount the span `[start, start + len)` of `store`, fetching it as whole
+/// 64-bit words (plus a trailing partial word) so the span isn't limited to
+/// the 64 bits a single `BitVector::get_bits` call can return.
+fn popcount_span(store: &BitVector, start: usize, len: usize) -> u64 {
+    let whole_words = len / 64;
+    let mut words = Vec::with_capacity(whole_words);
+    let mut pos = start;
+    for _ in 0..whole_words {
+        words.push(store.get_bits(pos, 64) as u64);
+        pos += 64;
+    }
+    let partial_bits = (len % 64) as u32;
+    let partial_word = if partial_bits > 0 {
+        store.get_bits(pos, partial_bits as usize) as u64
+    } else {
+        0
+    };
+    rank_bits_in_range(&words, partial_word, partial_bits)
+}
+
+/// Scan `store` once, recording the bit position of every `spacing`-th set
+/// bit into `ones_samples` and every `spacing`-th unset bit into
+/// `zeros_samples`, both 0-indexed (the first sample is the position of the
+/// very first one/zero).
+fn build_select_samples(store: &BitVector, spacing: u64) -> (CompactVector, CompactVector) {
+    let n = store.len() as u64;
+    let width = max(log2_ceil(n + 1), 1) as usize;
+    let ones_capacity = ceil_div(n, spacing.max(1)) as usize + 1;
+    let mut ones_samples = CompactVector::with_capacity(ones_capacity, width);
+    let mut zeros_samples = CompactVector::with_capacity(ones_capacity, width);
+    let mut ones_count = 0_u64;
+    let mut zeros_count = 0_u64;
+    for pos in 0..n {
+        if store.get_bit(pos as usize) {
+            if ones_count % spacing == 0 {
+                ones_samples.push(pos as usize);
+            }
+            ones_count += 1;
+        } else {
+            if zeros_count % spacing == 0 {
+                zeros_samples.push(pos as usize);
+            }
+            zeros_count += 1;
+        }
+    }
+    (ones_samples, zeros_samples)
 }
 
 impl RankSupport {
@@ -102,18 +192,21 @@ impl RankSupport {
             }
             block_cumulative_ranks.push(cumulative_rank - previous_cumulative_rank);
             let block_len = min(block_size, n - position as u64) as usize;
-            cumulative_rank +=
-                unsafe { popcount(store.get_bits(position, block_len) as u64) } as usize;
-            // cumulative_rank += store.get_bits(position, block_len).count_ones() as usize;
+            cumulative_rank += popcount_span(&store, position, block_len) as usize;
             position += block_len;
         }
 
+        let (ones_samples, zeros_samples) = build_select_samples(&store, SAMPLE_SPACING);
+
         Self {
             store,
             superblocks: superblock_cumulative_ranks,
             blocks: block_cumulative_ranks,
             s: superblock_size as u16,
             b: block_size as u8,
+            ones_samples,
+            zeros_samples,
+            sample_spacing: SAMPLE_SPACING,
         }
     }
 
@@ -132,12 +225,7 @@ impl RankSupport {
     pub fn rank1(&self, elem: u64) -> u64 {
         let superblock_position = (elem / self.s as u64) as usize;
         let (block_position, offset) = div_with_remainder(elem, self.b as u64);
-        let final_bits = unsafe {
-            popcount(
-                self.store
-                    .get_bits((elem - offset) as usize, offset as usize) as u64,
-            )
-        } as usize;
+        let final_bits = popcount_span(&self.store, (elem - offset) as usize, offset as usize) as usize;
         (self.superblocks.get(superblock_position)
             + self.blocks.get(block_position as usize)
             + final_bits) as u64
@@ -147,28 +235,145 @@ impl RankSupport {
         elem - self.rank1(elem)
     }
 
-    pub fn into_bytes(self) -> Result<Vec<u8>> {
-        let saveable: SaveableRankSupport = self.try_into()?;
-        bincode::serialize(&saveable).wrap_err("Failed to serialize rank support")
+    /// The number of ones in `[i, j)`.
+    pub fn range_rank1(&self, i: u64, j: u64) -> u64 {
+        self.rank1(j) - self.rank1(i)
     }
 
-    pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        self.clone().into_bytes()
+    /// Return the **first** position `j` such that `rank1(j) = value`, i.e.
+    /// one past the position of the `value`-th one bit (1-indexed), with
+    /// `select1(0) == 0`. Returns `self.store.len()` if there is no such bit.
+    pub fn select1(&self, value: u64) -> u64 {
+        if value == 0 {
+            return 0;
+        }
+        let Some((lo, hi)) = self.select1_bounds(value) else {
+            return self.store.len() as u64;
+        };
+        bisect_left(lo, hi, |x| self.rank1(x).cmp(&value))
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        bincode::deserialize(bytes).wrap_err("Failed to deserialize rank_support")
+    /// Symmetric to [`RankSupport::select1`] but over unset bits.
+    pub fn select0(&self, value: u64) -> u64 {
+        if value == 0 {
+            return 0;
+        }
+        let Some((lo, hi)) = self.select0_bounds(value) else {
+            return self.store.len() as u64;
+        };
+        bisect_left(lo, hi, |x| self.rank0(x).cmp(&value))
+    }
+
+    /// Return the smallest position `p >= i` with a set bit, or `None` if
+    /// there is no set bit at or after `i`.
+    pub fn next_one(&self, i: u64) -> Option<u64> {
+        let len = self.store.len() as u64;
+        let r = self.rank1(i);
+        if r == self.rank1(len) {
+            None
+        } else {
+            Some(self.select1(r + 1) - 1)
+        }
+    }
+
+    /// Return the largest position `p <= i` with a set bit, or `None` if
+    /// there is no set bit at or before `i`.
+    pub fn prev_one(&self, i: u64) -> Option<u64> {
+        let r = self.rank1(i + 1);
+        if r == 0 {
+            return None;
+        }
+        Some(self.select1(r) - 1)
+    }
+
+    /// Return the smallest position `p >= i` with an unset bit, or `None` if
+    /// there is no unset bit at or after `i`.
+    pub fn next_zero(&self, i: u64) -> Option<u64> {
+        let len = self.store.len() as u64;
+        let r = self.rank0(i);
+        if r == self.rank0(len) {
+            None
+        } else {
+            Some(self.select0(r + 1) - 1)
+        }
+    }
+
+    /// Return the largest position `p <= i` with an unset bit, or `None` if
+    /// there is no unset bit at or before `i`.
+    pub fn prev_zero(&self, i: u64) -> Option<u64> {
+        let r = self.rank0(i + 1);
+        if r == 0 {
+            return None;
+        }
+        Some(self.select0(r) - 1)
+    }
+
+    /// Narrow the search space for `select1(value)` down to the interval
+    /// `[lo, hi)` that is guaranteed to contain the answer, using the sampled
+    /// one-bit index built at construction time.
+    ///
+    /// Returns `None` when `value` is beyond the number of ones sampled,
+    /// meaning the caller should return `self.store.len()`.
+    pub(crate) fn select1_bounds(&self, value: u64) -> Option<(u64, u64)> {
+        let k = value.saturating_sub(1) / self.sample_spacing;
+        if k as usize >= self.ones_samples.len() {
+            return None;
+        }
+        let lo = self.ones_samples.get(k as usize) as u64;
+        let hi = if (k as usize + 1) < self.ones_samples.len() {
+            self.ones_samples.get(k as usize + 1) as u64
+        } else {
+            self.store.len() as u64
+        };
+        Some((lo, hi))
+    }
+
+    /// Symmetric to [`RankSupport::select1_bounds`] but over unset bits.
+    pub(crate) fn select0_bounds(&self, value: u64) -> Option<(u64, u64)> {
+        let k = value.saturating_sub(1) / self.sample_spacing;
+        if k as usize >= self.zeros_samples.len() {
+            return None;
+        }
+        let lo = self.zeros_samples.get(k as usize) as u64;
+        let hi = if (k as usize + 1) < self.zeros_samples.len() {
+            self.zeros_samples.get(k as usize + 1) as u64
+        } else {
+            self.store.len() as u64
+        };
+        Some((lo, hi))
     }
 
     /// The size in bits required to support constant time rank queries
     pub fn overhead(&self) -> u64 {
         (self.blocks.size_in_bytes()
             + self.superblocks.size_in_bytes()
+            + self.ones_samples.size_in_bytes()
+            + self.zeros_samples.size_in_bytes()
             + self.s.size_in_bytes()
             + self.b.size_in_bytes()
+            + self.sample_spacing.size_in_bytes()
             + size_of::<Rc<BitVector>> as usize
         ) as u64 * 8
     }
+}
+
+/// Bincode (de)serialization and the `save`/`load`/`save_mmap`/`load_mmap`
+/// on-disk paths. Kept behind the `std` feature (file I/O, `eyre`, `memmap2`)
+/// and separate from the `no_std` + `alloc`-compatible query core above.
+#[cfg(feature = "std")]
+impl RankSupport {
+    pub fn into_bytes(self) -> Result<Vec<u8>> {
+        let saveable: SaveableRankSupport = self.try_into()?;
+        bincode::serialize(&saveable).wrap_err("Failed to serialize rank support")
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        self.clone().into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).wrap_err("Failed to deserialize rank_support")
+    }
 
     pub fn save(&self, fname: &str) -> Result<()> {
         let file = File::create(fname).wrap_err(format!("Failed to create file {fname}"))?;
@@ -184,6 +389,268 @@ impl RankSupport {
         let result: RankSupport = bincode::deserialize_from(reader)?;
         Ok(result)
     }
+
+    /// Write this `RankSupport` in the fixed, section-aligned layout read by
+    /// [`RankSupport::load_mmap`]: a header recording `s`/`b`/`sample_spacing`
+    /// and the byte offset/length of each section, followed by the raw
+    /// 64-bit words of `store` (so it can be viewed in place without
+    /// deserializing), then the `sucds`-serialized overhead structures
+    /// (`superblocks`, `blocks`, `ones_samples`, `zeros_samples`), each
+    /// section padded up to an 8-byte boundary.
+    pub fn save_mmap(&self, fname: &str) -> Result<()> {
+        let store_words = store_words(&self.store);
+        let store_bytes: Vec<u8> = store_words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let superblocks_bytes = to_bytes(&self.superblocks)?;
+        let blocks_bytes = to_bytes(&self.blocks)?;
+        let ones_samples_bytes = to_bytes(&self.ones_samples)?;
+        let zeros_samples_bytes = to_bytes(&self.zeros_samples)?;
+
+        let sections = [
+            &store_bytes,
+            &superblocks_bytes,
+            &blocks_bytes,
+            &ones_samples_bytes,
+            &zeros_samples_bytes,
+        ];
+
+        let mut offset = MMAP_HEADER_LEN as u64;
+        let mut offsets_and_lens = [0_u64; 10];
+        for (i, section) in sections.iter().enumerate() {
+            offsets_and_lens[i * 2] = offset;
+            offsets_and_lens[i * 2 + 1] = section.len() as u64;
+            offset += aligned_len(section.len()) as u64;
+        }
+
+        let file = File::create(fname).wrap_err(format!("Failed to create file {fname}"))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&MMAP_MAGIC.to_le_bytes())?;
+        writer.write_all(&(self.store.len() as u64).to_le_bytes())?;
+        writer.write_all(&(self.s as u64).to_le_bytes())?;
+        writer.write_all(&(self.b as u64).to_le_bytes())?;
+        writer.write_all(&self.sample_spacing.to_le_bytes())?;
+        for value in offsets_and_lens {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for section in sections {
+            writer.write_all(section)?;
+            let padding = aligned_len(section.len()) - section.len();
+            writer.write_all(&vec![0_u8; padding])?;
+        }
+        Ok(())
+    }
+
+    /// Memory-map `fname` (written by [`RankSupport::save_mmap`]) and return a
+    /// handle that resolves `rank1`/`rank0`/`select1`/`select0` by indexing
+    /// directly into the mapped bytes, rather than copying the whole
+    /// structure into owned heap allocations the way [`RankSupport::load`]
+    /// does. The small overhead structures (`superblocks`, `blocks`, and the
+    /// select samples) are still eagerly decoded since they are tiny relative
+    /// to `store`; only the bitvector itself stays resident as a mapped view.
+    pub fn load_mmap(fname: &str) -> Result<MmapRankSupport> {
+        let file = File::open(fname).wrap_err(format!("Failed to open file {fname}"))?;
+        let mmap = unsafe { Mmap::map(&file) }.wrap_err("Failed to mmap rank support file")?;
+
+        let magic = u64::from_le_bytes(mmap[0..8].try_into()?);
+        if magic != MMAP_MAGIC {
+            return Err(eyre!("{fname} is not a RankSupport mmap file"));
+        }
+        let n = u64::from_le_bytes(mmap[8..16].try_into()?);
+        let s = u64::from_le_bytes(mmap[16..24].try_into()?) as u16;
+        let b = u64::from_le_bytes(mmap[24..32].try_into()?) as u8;
+        let sample_spacing = u64::from_le_bytes(mmap[32..40].try_into()?);
+
+        let mut offsets_and_lens = [0_u64; 10];
+        for (i, slot) in offsets_and_lens.iter_mut().enumerate() {
+            let start = 40 + i * 8;
+            *slot = u64::from_le_bytes(mmap[start..start + 8].try_into()?);
+        }
+
+        let section = |i: usize| -> &[u8] {
+            let offset = offsets_and_lens[i * 2] as usize;
+            let len = offsets_and_lens[i * 2 + 1] as usize;
+            &mmap[offset..offset + len]
+        };
+
+        let superblocks: CompactVector = from_bytes(section(1).to_vec())?;
+        let blocks: CompactVector = from_bytes(section(2).to_vec())?;
+        let ones_samples: CompactVector = from_bytes(section(3).to_vec())?;
+        let zeros_samples: CompactVector = from_bytes(section(4).to_vec())?;
+
+        Ok(MmapRankSupport {
+            mmap,
+            n,
+            s,
+            b,
+            sample_spacing,
+            store_offset: offsets_and_lens[0] as usize,
+            store_len: offsets_and_lens[1] as usize,
+            superblocks,
+            blocks,
+            ones_samples,
+            zeros_samples,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+const MMAP_MAGIC: u64 = 0x52414e4b_53555030; // b"RANKSUP0" read as a big-endian u64
+// magic(8) + n(8) + s(8) + b(8) + sample_spacing(8) + 5 sections * (offset(8) + len(8))
+#[cfg(feature = "std")]
+const MMAP_HEADER_LEN: usize = 8 * 5 + 8 * 10;
+
+/// Extract the raw 64-bit words backing `store`'s bits, in the same
+/// little-endian, LSB-first packing `get_bits_from_words` assumes when
+/// reading them back out of a mapped byte slice.
+#[cfg(feature = "std")]
+fn store_words(store: &BitVector) -> Vec<u64> {
+    let n = store.len() as u64;
+    let mut words = Vec::with_capacity(ceil_div(n, 64) as usize);
+    let mut pos = 0_u64;
+    while pos < n {
+        let len = min(64, n - pos) as usize;
+        words.push(store.get_bits(pos as usize, len) as u64);
+        pos += len as u64;
+    }
+    words
+}
+
+/// Read `len` (<= 64) bits starting at bit position `pos` out of a raw,
+/// little-endian, LSB-first packed `words` slice, mirroring
+/// `sucds::BitVector::get_bits`.
+#[cfg(feature = "std")]
+fn get_bits_from_words(words: &[u64], pos: u64, len: u64) -> u64 {
+    if len == 0 {
+        return 0;
+    }
+    let word_idx = (pos / 64) as usize;
+    let bit_offset = pos % 64;
+    let mut value = words[word_idx] >> bit_offset;
+    let read = 64 - bit_offset;
+    if read < len {
+        value |= words[word_idx + 1] << read;
+    }
+    if len < 64 {
+        value &= (1_u64 << len) - 1;
+    }
+    value
+}
+
+#[cfg(feature = "std")]
+fn popcount_words_range(words: &[u64], start: u64, len: u64) -> u64 {
+    let whole_words = (len / 64) as usize;
+    let mut whole = Vec::with_capacity(whole_words);
+    let mut pos = start;
+    for _ in 0..whole_words {
+        whole.push(get_bits_from_words(words, pos, 64));
+        pos += 64;
+    }
+    let partial_bits = (len % 64) as u32;
+    let partial_word = if partial_bits > 0 {
+        get_bits_from_words(words, pos, partial_bits as u64)
+    } else {
+        0
+    };
+    rank_bits_in_range(&whole, partial_word, partial_bits)
+}
+
+/// A `RankSupport`-equivalent view over a memory-mapped file produced by
+/// [`RankSupport::save_mmap`]. `store`'s bits are read directly out of the
+/// mapped bytes rather than materialized into an owned `BitVector`, so
+/// opening even a multi-gigabyte structure only costs the page faults its
+/// queries actually touch.
+#[cfg(feature = "std")]
+pub struct MmapRankSupport {
+    mmap: Mmap,
+    n: u64,
+    s: u16,
+    b: u8,
+    sample_spacing: u64,
+    store_offset: usize,
+    store_len: usize,
+    superblocks: CompactVector,
+    blocks: CompactVector,
+    ones_samples: CompactVector,
+    zeros_samples: CompactVector,
+}
+
+#[cfg(feature = "std")]
+impl MmapRankSupport {
+    fn store_words(&self) -> &[u64] {
+        // SAFETY: `store_offset` and `store_len` are the section recorded by
+        // `save_mmap`, which always aligns sections to 8 bytes and writes
+        // `store` as whole little-endian `u64` words.
+        unsafe { bytes_as_u64_slice(&self.mmap[self.store_offset..self.store_offset + self.store_len]) }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn rank1(&self, elem: u64) -> u64 {
+        let words = self.store_words();
+        let superblock_position = (elem / self.s as u64) as usize;
+        let (block_position, offset) = div_with_remainder(elem, self.b as u64);
+        let final_bits = popcount_words_range(words, elem - offset, offset);
+        (self.superblocks.get(superblock_position)
+            + self.blocks.get(block_position as usize)
+            + final_bits as usize) as u64
+    }
+
+    pub fn rank0(&self, elem: u64) -> u64 {
+        elem - self.rank1(elem)
+    }
+
+    fn select1_bounds(&self, value: u64) -> Option<(u64, u64)> {
+        let k = value.saturating_sub(1) / self.sample_spacing;
+        if k as usize >= self.ones_samples.len() {
+            return None;
+        }
+        let lo = self.ones_samples.get(k as usize) as u64;
+        let hi = if (k as usize + 1) < self.ones_samples.len() {
+            self.ones_samples.get(k as usize + 1) as u64
+        } else {
+            self.n
+        };
+        Some((lo, hi))
+    }
+
+    fn select0_bounds(&self, value: u64) -> Option<(u64, u64)> {
+        let k = value.saturating_sub(1) / self.sample_spacing;
+        if k as usize >= self.zeros_samples.len() {
+            return None;
+        }
+        let lo = self.zeros_samples.get(k as usize) as u64;
+        let hi = if (k as usize + 1) < self.zeros_samples.len() {
+            self.zeros_samples.get(k as usize + 1) as u64
+        } else {
+            self.n
+        };
+        Some((lo, hi))
+    }
+
+    pub fn select1(&self, value: u64) -> u64 {
+        if value == 0 {
+            return 0;
+        }
+        let Some((lo, hi)) = self.select1_bounds(value) else {
+            return self.n;
+        };
+        bisect_left(lo, hi, |x| self.rank1(x).cmp(&value))
+    }
+
+    pub fn select0(&self, value: u64) -> u64 {
+        if value == 0 {
+            return 0;
+        }
+        let Some((lo, hi)) = self.select0_bounds(value) else {
+            return self.n;
+        };
+        bisect_left(lo, hi, |x| self.rank0(x).cmp(&value))
+    }
 }
 
 #[cfg(test)]
@@ -233,6 +700,43 @@ mod tests {
         assert_eq!(2, rs.rank1(4));
     }
 
+    #[test]
+    fn test_next_prev_one() {
+        // ones at 1, 2, 4
+        let bv = BitVector::from_bits([false, true, true, false, true]);
+        let rs = RankSupport::new_from_owned(bv);
+        assert_eq!(Some(1), rs.next_one(0));
+        assert_eq!(Some(1), rs.next_one(1));
+        assert_eq!(Some(2), rs.next_one(2));
+        assert_eq!(Some(4), rs.next_one(3));
+        assert_eq!(Some(4), rs.next_one(4));
+        assert_eq!(None, rs.next_one(5));
+
+        assert_eq!(None, rs.prev_one(0));
+        assert_eq!(Some(1), rs.prev_one(1));
+        assert_eq!(Some(2), rs.prev_one(2));
+        assert_eq!(Some(2), rs.prev_one(3));
+        assert_eq!(Some(4), rs.prev_one(4));
+    }
+
+    #[test]
+    fn test_next_prev_zero() {
+        // zeros at 0, 3
+        let bv = BitVector::from_bits([false, true, true, false, true]);
+        let rs = RankSupport::new_from_owned(bv);
+        assert_eq!(Some(0), rs.next_zero(0));
+        assert_eq!(Some(3), rs.next_zero(1));
+        assert_eq!(Some(3), rs.next_zero(2));
+        assert_eq!(Some(3), rs.next_zero(3));
+        assert_eq!(None, rs.next_zero(4));
+
+        assert_eq!(Some(0), rs.prev_zero(0));
+        assert_eq!(Some(0), rs.prev_zero(1));
+        assert_eq!(Some(0), rs.prev_zero(2));
+        assert_eq!(Some(3), rs.prev_zero(3));
+        assert_eq!(Some(3), rs.prev_zero(4));
+    }
+
     #[test]
     fn test_various_sizes() {
         let mut rng = StdRng::from_entropy();
@@ -1,8 +1,13 @@
+#[cfg(feature = "std")]
 use std::rc::Rc;
 
+#[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+
+#[cfg(feature = "std")]
 use eyre::Result;
 
-use crate::{binary_search::bisect_left, rank_support::RankSupport};
+use crate::rank_support::RankSupport;
 
 #[derive(Debug)]
 pub struct SelectSupport {
@@ -35,21 +40,20 @@ impl SelectSupport {
     /// assert_eq!(5, s.select1(3));
     /// ```
     pub fn select1(&self, value: u64) -> u64 {
-        bisect_left(0, self.rank_support.store.len() as u64, |x| {
-            self.rank_support.rank1(x).cmp(&value)
-        })
+        self.rank_support.select1(value)
     }
 
     pub fn select0(&self, value: u64) -> u64 {
-        bisect_left(0, self.rank_support.store.len() as u64, |x| {
-            self.rank_support.rank0(x).cmp(&value)
-        })
+        self.rank_support.select0(value)
     }
 
     pub fn overhead(&self) -> u64 {
         self.rank_support.overhead()
     }
+}
 
+#[cfg(feature = "std")]
+impl SelectSupport {
     pub fn save(&self, fname: &str) -> Result<()> {
         self.rank_support.save(fname)?;
         Ok(())
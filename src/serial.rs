@@ -30,3 +30,23 @@ pub fn from_bytes<T: Searial>(bytes: Vec<u8>) -> Result<T> {
         Err(error) => Err(eyre!("Could not deserialize the BitVector: {error:?}"))?,
     }
 }
+
+/// Byte alignment used by the fixed on-disk layout that
+/// `RankSupport::save_mmap`/`load_mmap` read and write, so every section
+/// starts at an offset safe to reinterpret as `&[u64]`.
+pub const MMAP_ALIGNMENT: usize = 8;
+
+/// Round `len` up to the next multiple of [`MMAP_ALIGNMENT`].
+pub fn aligned_len(len: usize) -> usize {
+    (len + MMAP_ALIGNMENT - 1) / MMAP_ALIGNMENT * MMAP_ALIGNMENT
+}
+
+/// Reinterpret a byte slice as a slice of `u64` words without copying.
+///
+/// # Safety
+/// `bytes` must start at an 8-byte aligned address and have a length that is
+/// a multiple of 8. Both are guaranteed by the mmap section layout written by
+/// `RankSupport::save_mmap`.
+pub unsafe fn bytes_as_u64_slice(bytes: &[u8]) -> &[u64] {
+    std::slice::from_raw_parts(bytes.as_ptr() as *const u64, bytes.len() / 8)
+}
@@ -1,14 +1,32 @@
+#[cfg(feature = "std")]
 use std::{
+    cell::RefCell,
+    cmp::min,
+    collections::HashMap,
     fs::File,
-    io::{BufReader, BufWriter},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
     rc::Rc,
 };
 
-use eyre::{Context, Result};
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(not(feature = "std"))]
+use alloc::{rc::Rc, vec::Vec};
+
+#[cfg(feature = "std")]
+use eyre::{eyre, Context, Result};
+#[cfg(feature = "std")]
+use memmap2::Mmap;
+use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "std"))]
+use serde::Serialize;
+// `sucds` is itself a std-only crate; see the crate-root note on why the
+// `no_std` gating here is aspirational rather than link-verified.
 use sucds::{BitVector, Searial};
 
 use crate::{rank_support::RankSupport, select_support::SelectSupport};
+#[cfg(feature = "std")]
+use crate::serial::aligned_len;
 
 #[derive(Debug, Clone)]
 pub struct SparseArray<T> {
@@ -23,12 +41,14 @@ pub struct SparseArrayBuilder<T> {
     store: BitVector,
 }
 
+#[cfg(feature = "std")]
 #[derive(Serialize, Deserialize)]
 struct InterimSparseArray<T> {
     pub rank_support_bytes: Vec<u8>,
     pub vector: Vec<T>,
 }
 
+#[cfg(feature = "std")]
 impl<T: Serialize + Clone> TryFrom<InterimSparseArray<T>> for SparseArray<T> {
     type Error = eyre::Report;
 
@@ -73,6 +93,62 @@ impl<T: Serialize + Clone + DeserializeOwned> SparseArrayBuilder<T> {
     }
 }
 
+/// A [`SparseArrayBuilder`] variant that backs its bitmap with a fixed-size,
+/// stack-allocated array of `WORDS` 64-bit words (`WORDS * 64` addressable
+/// positions) instead of the heap-allocated `sucds::BitVector`, so embedded
+/// callers with a known maximum length can accumulate positions without any
+/// allocation for the bitmap itself. `finalize` still builds the same
+/// `RankSupport`/`SelectSupport` pair [`SparseArrayBuilder::finalize`] does,
+/// so the resulting [`SparseArray`] has an identical query API.
+#[derive(Debug)]
+pub struct FixedCapacitySparseArrayBuilder<T, const WORDS: usize> {
+    vector: Vec<T>,
+    words: [u64; WORDS],
+    len: usize,
+}
+
+impl<T: Serialize + Clone + DeserializeOwned, const WORDS: usize>
+    FixedCapacitySparseArrayBuilder<T, WORDS>
+{
+    /// The number of addressable bit positions, `WORDS * 64`.
+    pub fn capacity() -> usize {
+        WORDS * 64
+    }
+
+    /// `len` is the number of positions actually in use; it must be `<=
+    /// Self::capacity()`.
+    pub fn new(len: usize) -> Self {
+        assert!(
+            len <= Self::capacity(),
+            "len {len} exceeds fixed capacity of {} bits",
+            Self::capacity()
+        );
+        Self {
+            vector: Vec::new(),
+            words: [0_u64; WORDS],
+            len,
+        }
+    }
+
+    pub fn append(&mut self, value: T, pos: u64) {
+        let pos = pos as usize;
+        self.words[pos / 64] |= 1_u64 << (pos % 64);
+        self.vector.push(value);
+    }
+
+    /// Build all support structures and return final locked sparse array.
+    pub fn finalize(self) -> SparseArray<T> {
+        let bits: Vec<bool> = (0..self.len)
+            .map(|i| (self.words[i / 64] >> (i % 64)) & 1 == 1)
+            .collect();
+        SparseArrayBuilder {
+            vector: self.vector,
+            store: BitVector::from_bits(bits),
+        }
+        .finalize()
+    }
+}
+
 impl<T: Serialize + Clone + DeserializeOwned> SparseArray<T> {
     // Generate a static SparseArray from parts
     pub fn new(vector: Vec<T>, store: BitVector) -> Self {
@@ -159,6 +235,20 @@ impl<T: Serialize + Clone + DeserializeOwned> SparseArray<T> {
         self.rank_support.rank1(index + 1)
     }
 
+    /// Exposes the underlying rank structure directly, for callers (such as
+    /// the experiment harness) that want to benchmark rank queries without
+    /// going through `vector`.
+    pub fn rank_support(&self) -> &Rc<RankSupport> {
+        &self.rank_support
+    }
+
+    /// Exposes the underlying select structure directly, for callers (such as
+    /// the experiment harness) that want to benchmark select queries without
+    /// going through `vector`.
+    pub fn select_support(&self) -> &Rc<SelectSupport> {
+        &self.select_support
+    }
+
     pub fn get_at_rank(&self, rank: u64) -> Option<&T> {
         self.vector.get(rank as usize)
     }
@@ -196,7 +286,13 @@ impl<T: Serialize + Clone + DeserializeOwned> SparseArray<T> {
 
         Some(self.select_support.select1(rank) - 1)
     }
+}
 
+/// Bincode save/load, the block-compressed mmap layout, and XML dump/restore.
+/// Kept behind the `std` feature (file I/O, `eyre`, `memmap2`, `zstd`) and
+/// separate from the `no_std` + `alloc`-compatible query core above.
+#[cfg(feature = "std")]
+impl<T: Serialize + Clone + DeserializeOwned> SparseArray<T> {
     pub fn load(fname: &str) -> Result<Self> {
         let file = File::open(fname)?;
         let reader = BufReader::new(file);
@@ -227,6 +323,353 @@ impl<T: Serialize + Clone + DeserializeOwned> SparseArray<T> {
             .wrap_err("Failed to serialize sparse array")?;
         Ok(())
     }
+
+    /// Write this array in the block-compressed layout read by
+    /// [`MmapSparseArray::load`]: a header, the rank-support bytes (kept
+    /// uncompressed since rank/select needs the whole bitmap resident), then
+    /// an offset table recording each compressed block's byte offset,
+    /// compressed length and element count, followed by `vector` split into
+    /// fixed-size blocks of `block_size` elements, each bincode-then-zstd
+    /// compressed.
+    ///
+    /// `block_size` trades random-access latency against compression ratio:
+    /// smaller blocks decompress faster per access but compress worse.
+    /// [`DEFAULT_MMAP_BLOCK_SIZE`] (256) is a reasonable default.
+    pub fn save_mmap(&self, fname: &str, block_size: usize) -> Result<()> {
+        let rank_support_bytes = self.rank_support.to_bytes()?;
+
+        let compressed_blocks: Vec<Vec<u8>> = self
+            .vector
+            .chunks(block_size.max(1))
+            .map(|chunk| -> Result<Vec<u8>> {
+                let encoded = bincode::serialize(chunk).wrap_err("Failed to encode block")?;
+                zstd::stream::encode_all(&encoded[..], 0).wrap_err("Failed to compress block")
+            })
+            .collect::<Result<_>>()?;
+
+        let mut offset_table = Vec::<(u64, u64, u64)>::with_capacity(compressed_blocks.len());
+        let mut offset = MMAP_HEADER_LEN as u64
+            + aligned_len(rank_support_bytes.len()) as u64
+            + (compressed_blocks.len() * 24) as u64;
+        for (i, block) in compressed_blocks.iter().enumerate() {
+            let elem_count = min(block_size, self.vector.len() - i * block_size);
+            offset_table.push((offset, block.len() as u64, elem_count as u64));
+            offset += aligned_len(block.len()) as u64;
+        }
+
+        let file = File::create(fname).wrap_err(format!("Failed to create file {fname}"))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&MMAP_MAGIC.to_le_bytes())?;
+        writer.write_all(&(self.num_elem()).to_le_bytes())?;
+        writer.write_all(&(block_size as u64).to_le_bytes())?;
+        writer.write_all(&(rank_support_bytes.len() as u64).to_le_bytes())?;
+        writer.write_all(&(compressed_blocks.len() as u64).to_le_bytes())?;
+        writer.write_all(&rank_support_bytes)?;
+        writer.write_all(&vec![0_u8; aligned_len(rank_support_bytes.len()) - rank_support_bytes.len()])?;
+        for (block_offset, block_len, elem_count) in &offset_table {
+            writer.write_all(&block_offset.to_le_bytes())?;
+            writer.write_all(&block_len.to_le_bytes())?;
+            writer.write_all(&elem_count.to_le_bytes())?;
+        }
+        for block in &compressed_blocks {
+            writer.write_all(block)?;
+            writer.write_all(&vec![0_u8; aligned_len(block.len()) - block.len()])?;
+        }
+        Ok(())
+    }
+
+    /// Stream this array out as human-readable XML: a `<sparse_array>` root
+    /// carrying `size`/`num_elem` attributes, with one `<element index="I"
+    /// rank="R">` child per present slot, its value encoded via
+    /// `serde_json`. Written incrementally (one element at a time) rather
+    /// than buffered, so large arrays don't need a second in-memory copy.
+    /// Pairs with [`SparseArray::restore`] to diff, hand-edit, or migrate
+    /// sparse contents across bincode layout changes.
+    pub fn dump<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(
+            w,
+            "<sparse_array size=\"{}\" num_elem=\"{}\">",
+            self.size(),
+            self.num_elem()
+        )?;
+        let mut rank = 0_u64;
+        for index in 0..self.size() {
+            if self.rank_support.store.get_bit(index as usize) {
+                let value = &self.vector[rank as usize];
+                let json = serde_json::to_string(value).wrap_err("Failed to encode element")?;
+                writeln!(
+                    w,
+                    "  <element index=\"{index}\" rank=\"{rank}\">{}</element>",
+                    xml_escape(&json)
+                )?;
+                rank += 1;
+            }
+        }
+        writeln!(w, "</sparse_array>")?;
+        Ok(())
+    }
+
+    /// Parse the XML format written by [`SparseArray::dump`] back into a
+    /// `SparseArray`, reading line-by-line (a simple pull parser, not a full
+    /// DOM) so restoring doesn't materialize the document as one string.
+    pub fn restore<R: Read>(r: R) -> Result<Self> {
+        let reader = BufReader::new(r);
+        let mut builder: Option<SparseArrayBuilder<T>> = None;
+        for line in reader.lines() {
+            let line = line.wrap_err("Failed to read a line of the XML dump")?;
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed.strip_prefix("<sparse_array ") {
+                let size = parse_xml_attr(rest, "size")
+                    .ok_or_else(|| eyre!("<sparse_array> is missing a size attribute"))?;
+                builder = Some(SparseArrayBuilder::new(size));
+            } else if let Some(rest) = trimmed.strip_prefix("<element ") {
+                let index = parse_xml_attr(rest, "index")
+                    .ok_or_else(|| eyre!("<element> is missing an index attribute"))?;
+                let tag_end = rest
+                    .find('>')
+                    .ok_or_else(|| eyre!("<element> tag is missing a closing '>'"))?;
+                let body = &rest[tag_end + 1..];
+                let text_end = body
+                    .find("</element>")
+                    .ok_or_else(|| eyre!("<element> is missing a closing tag"))?;
+                let json = xml_unescape(&body[..text_end]);
+                let value: T =
+                    serde_json::from_str(&json).wrap_err("Failed to decode element value")?;
+                builder
+                    .as_mut()
+                    .ok_or_else(|| eyre!("<element> appeared before <sparse_array>"))?
+                    .append(value, index);
+            }
+        }
+        let builder = builder.ok_or_else(|| eyre!("missing <sparse_array> root element"))?;
+        Ok(builder.finalize())
+    }
+}
+
+/// Find `name="..."` within an XML start tag's remaining attribute text and
+/// parse the quoted value.
+#[cfg(feature = "std")]
+fn parse_xml_attr(tag: &str, name: &str) -> Option<u64> {
+    let needle = format!("{name}=\"");
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')? + start;
+    tag[start..end].parse().ok()
+}
+
+#[cfg(feature = "std")]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(feature = "std")]
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Default number of elements per compressed block in the `save_mmap`/
+/// [`MmapSparseArray`] layout. Smaller blocks cost less decompression
+/// amplification per random access; larger blocks compress better.
+#[cfg(feature = "std")]
+pub const DEFAULT_MMAP_BLOCK_SIZE: usize = 256;
+
+#[cfg(feature = "std")]
+const MMAP_MAGIC: u64 = 0x53504152_53455130; // b"SPARSEQ0" read as a big-endian u64
+// magic(8) + num_elem(8) + block_size(8) + rank_support_len(8) + num_blocks(8)
+#[cfg(feature = "std")]
+const MMAP_HEADER_LEN: usize = 8 * 5;
+
+/// Size, in bytes, of the resident portion of an [`MmapSparseArray`]: the
+/// fully-loaded rank support plus the offset table. Reported separately from
+/// the on-disk compressed value payload, which stays paged in by the OS and
+/// is only decompressed on demand.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct MmapOverhead {
+    pub resident_bytes: u64,
+    pub compressed_payload_bytes: u64,
+}
+
+/// A [`SparseArray`]-equivalent view over a file written by
+/// [`SparseArray::save_mmap`]. `vector` is never materialized in full:
+/// `get_at_rank` decompresses only the block containing the requested rank,
+/// keeping the last few decompressed blocks in a small LRU cache, so RSS
+/// stays roughly `O(bitvector size)` regardless of how large the values are.
+#[cfg(feature = "std")]
+pub struct MmapSparseArray<T> {
+    mmap: Mmap,
+    rank_support: Rc<RankSupport>,
+    select_support: Rc<SelectSupport>,
+    block_size: usize,
+    num_elem: u64,
+    // (offset, compressed length, element count) per block
+    offset_table: Vec<(u64, u64, u64)>,
+    cache: RefCell<BlockCache<T>>,
+}
+
+#[cfg(feature = "std")]
+const CACHED_BLOCKS: usize = 4;
+
+#[cfg(feature = "std")]
+struct BlockCache<T> {
+    capacity: usize,
+    // most-recently-used block index at the back
+    order: Vec<usize>,
+    blocks: HashMap<usize, Rc<Vec<T>>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> BlockCache<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: Vec::with_capacity(capacity),
+            blocks: HashMap::with_capacity(capacity),
+        }
+    }
+
+    fn get(&mut self, block: usize) -> Option<Rc<Vec<T>>> {
+        if let Some(value) = self.blocks.get(&block) {
+            let value = Rc::clone(value);
+            self.touch(block);
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn touch(&mut self, block: usize) {
+        self.order.retain(|&b| b != block);
+        self.order.push(block);
+    }
+
+    fn insert(&mut self, block: usize, value: Rc<Vec<T>>) {
+        if self.blocks.len() >= self.capacity && !self.blocks.contains_key(&block) {
+            let oldest = self.order.remove(0);
+            self.blocks.remove(&oldest);
+        }
+        self.blocks.insert(block, value);
+        self.touch(block);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: DeserializeOwned + Clone> MmapSparseArray<T> {
+    pub fn load(fname: &str) -> Result<Self> {
+        let file = File::open(fname).wrap_err(format!("Failed to open file {fname}"))?;
+        let mmap = unsafe { Mmap::map(&file) }.wrap_err("Failed to mmap sparse array file")?;
+
+        let magic = u64::from_le_bytes(mmap[0..8].try_into()?);
+        if magic != MMAP_MAGIC {
+            return Err(eyre!("{fname} is not an MmapSparseArray file"));
+        }
+        let num_elem = u64::from_le_bytes(mmap[8..16].try_into()?);
+        let block_size = u64::from_le_bytes(mmap[16..24].try_into()?) as usize;
+        let rank_support_len = u64::from_le_bytes(mmap[24..32].try_into()?) as usize;
+        let num_blocks = u64::from_le_bytes(mmap[32..40].try_into()?) as usize;
+
+        let rank_support_start = MMAP_HEADER_LEN;
+        let rank_support_bytes = &mmap[rank_support_start..rank_support_start + rank_support_len];
+        let rank_support = Rc::new(RankSupport::from_bytes(rank_support_bytes)?);
+        let select_support = Rc::new(SelectSupport::new(Rc::clone(&rank_support)));
+
+        let table_start = rank_support_start + aligned_len(rank_support_len);
+        let mut offset_table = Vec::with_capacity(num_blocks);
+        for i in 0..num_blocks {
+            let start = table_start + i * 24;
+            let offset = u64::from_le_bytes(mmap[start..start + 8].try_into()?);
+            let len = u64::from_le_bytes(mmap[start + 8..start + 16].try_into()?);
+            let elem_count = u64::from_le_bytes(mmap[start + 16..start + 24].try_into()?);
+            offset_table.push((offset, len, elem_count));
+        }
+
+        Ok(Self {
+            mmap,
+            rank_support,
+            select_support,
+            block_size,
+            num_elem,
+            offset_table,
+            cache: RefCell::new(BlockCache::new(CACHED_BLOCKS)),
+        })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.rank_support.store.len() as u64
+    }
+
+    pub fn num_elem(&self) -> u64 {
+        self.num_elem
+    }
+
+    pub fn num_elem_at(&self, index: u64) -> u64 {
+        self.rank_support.rank1(index + 1)
+    }
+
+    pub fn get_index_of(&self, rank: u64) -> Option<u64> {
+        if rank > self.num_elem || rank == 0 {
+            return None;
+        }
+        Some(self.select_support.select1(rank) - 1)
+    }
+
+    fn decompress_block(&self, block: usize) -> Result<Rc<Vec<T>>> {
+        if let Some(cached) = self.cache.borrow_mut().get(block) {
+            return Ok(cached);
+        }
+        let (offset, len, _) = self.offset_table[block];
+        let compressed = &self.mmap[offset as usize..(offset + len) as usize];
+        let decoded = zstd::stream::decode_all(compressed).wrap_err("Failed to decompress block")?;
+        let values: Vec<T> = bincode::deserialize(&decoded).wrap_err("Failed to decode block")?;
+        let values = Rc::new(values);
+        self.cache.borrow_mut().insert(block, Rc::clone(&values));
+        Ok(values)
+    }
+
+    /// Returns the `rank`-th stored value (cloned out of a decompressed,
+    /// possibly cached block), or `None` if `rank` is out of range.
+    pub fn get_at_rank(&self, rank: u64) -> Option<T> {
+        if rank >= self.num_elem {
+            return None;
+        }
+        let block = (rank as usize) / self.block_size.max(1);
+        let within_block = (rank as usize) % self.block_size.max(1);
+        let values = self.decompress_block(block).ok()?;
+        values.get(within_block).cloned()
+    }
+
+    pub fn get_at_index(&self, index: u64) -> Option<T> {
+        if index >= self.size() {
+            return None;
+        }
+        match self.rank_support.store.get_bit(index as usize) {
+            true => self.get_at_rank(self.rank_support.rank1(index)),
+            false => None,
+        }
+    }
+
+    /// Size in bytes of the resident bitmap/offset-table overhead, reported
+    /// separately from the on-disk compressed value payload.
+    pub fn overhead(&self) -> MmapOverhead {
+        // `RankSupport::overhead` is in bits; each offset-table entry is 24 bytes.
+        let resident_bytes = self.rank_support.store.size_in_bytes() as u64
+            + self.select_support.overhead() / 8
+            + (self.offset_table.len() * 24) as u64;
+        let compressed_payload_bytes = self
+            .offset_table
+            .iter()
+            .map(|&(_, len, _)| len)
+            .sum();
+        MmapOverhead {
+            resident_bytes,
+            compressed_payload_bytes,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -247,6 +690,46 @@ mod tests {
         assert_eq!(loaded.num_elem_at(1), 1);
     }
 
+    #[test]
+    fn test_dump_restore() {
+        let sa = SparseArray::<u32>::from_dense_vec(vec![None, Some(1), None, Some(2), Some(3)]);
+        let mut xml = Vec::<u8>::new();
+        sa.dump(&mut xml).unwrap();
+        let restored: SparseArray<u32> = SparseArray::restore(xml.as_slice()).unwrap();
+        assert_eq!(restored.size(), sa.size());
+        assert_eq!(restored.num_elem(), sa.num_elem());
+        for index in 0..sa.size() {
+            assert_eq!(restored.get_at_index(index), sa.get_at_index(index));
+        }
+    }
+
+    #[test]
+    fn test_save_mmap_load() {
+        let sa = SparseArray::<u32>::from_dense_vec(vec![None, Some(1), None, Some(2), Some(3)]);
+        sa.save_mmap("tmp-mmap-file.bin", 2).unwrap();
+        let loaded: MmapSparseArray<u32> = MmapSparseArray::load("tmp-mmap-file.bin").unwrap();
+        assert_eq!(loaded.size(), sa.size());
+        assert_eq!(loaded.num_elem(), sa.num_elem());
+        for index in 0..sa.size() {
+            assert_eq!(loaded.get_at_index(index), sa.get_at_index(index).copied());
+        }
+    }
+
+    #[test]
+    fn test_fixed_capacity_builder() {
+        let mut builder = FixedCapacitySparseArrayBuilder::<u32, 2>::new(100);
+        builder.append(1, 1);
+        builder.append(2, 3);
+        builder.append(3, 99);
+        let sparse = builder.finalize();
+        assert_eq!(sparse.size(), 100);
+        assert_eq!(sparse.num_elem(), 3);
+        assert_eq!(*sparse.get_at_index(1).unwrap(), 1);
+        assert_eq!(*sparse.get_at_index(3).unwrap(), 2);
+        assert_eq!(*sparse.get_at_index(99).unwrap(), 3);
+        assert_eq!(sparse.get_at_index(0), None);
+    }
+
     #[test]
     fn test_from_dense_vec() {
         let distribution = Uniform::new_inclusive(0, 100_u8);